@@ -0,0 +1,429 @@
+use std::str::FromStr;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::error::ColorError;
+
+/// srgb color with straight alpha, 8 bits per channel like the gpu reads it,
+/// the renderer converts it to linear space before anything is blended
+#[derive(Pod, Zeroable, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// the css named colors, `transparent` is handled on its own
+const NAMED: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+/// decodes an srgb channel in 0..=1 to linear light
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// encodes a linear channel in 0..=1 as srgb
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn unorm(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Color {
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+    /// `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` like css, the `#` is optional
+    pub fn hex(hex: &str) -> Result<Self, ColorError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let mut values = Vec::with_capacity(8);
+        for c in digits.chars() {
+            values.push(c.to_digit(16).ok_or(ColorError::InvalidDigit(c))? as u8);
+        }
+        let channels: Vec<u8> = match values.len() {
+            3 | 4 => values.iter().map(|v| v * 17).collect(),
+            6 | 8 => values.chunks(2).map(|v| v[0] * 16 + v[1]).collect(),
+            len => return Err(ColorError::InvalidLength(len)),
+        };
+        let a = channels.get(3).copied().unwrap_or(255);
+        Ok(Self::rgba(channels[0], channels[1], channels[2], a))
+    }
+    /// one of the css named colors or `transparent`, ignoring case
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Self::TRANSPARENT);
+        }
+        NAMED.iter().find(|(n, _)| *n == name).map(|&(_, rgb)| {
+            let [_, r, g, b] = rgb.to_be_bytes();
+            Self::rgb(r, g, b)
+        })
+    }
+    /// `hue` in degrees, `saturation` and `lightness` from 0 to 1
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let l = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * saturation.clamp(0.0, 1.0);
+        Self::from_chroma(hue, chroma, l - chroma / 2.0)
+    }
+    /// `hue` in degrees, `saturation` and `value` from 0 to 1
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let v = value.clamp(0.0, 1.0);
+        let chroma = v * saturation.clamp(0.0, 1.0);
+        Self::from_chroma(hue, chroma, v - chroma)
+    }
+    /// the rgb channels of a hue with `chroma`, each lifted by `min`
+    fn from_chroma(hue: f32, chroma: f32, min: f32) -> Self {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let [r, g, b] = match h as u32 {
+            0 => [chroma, x, 0.0],
+            1 => [x, chroma, 0.0],
+            2 => [0.0, chroma, x],
+            3 => [0.0, x, chroma],
+            4 => [x, 0.0, chroma],
+            _ => [chroma, 0.0, x],
+        };
+        Self::rgb(unorm(r + min), unorm(g + min), unorm(b + min))
+    }
+    /// hue in degrees, and the smallest channel, the largest one and the chroma from 0 to 1
+    fn hue(self) -> [f32; 4] {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        [hue * 60.0, min, max, chroma]
+    }
+    /// hue in degrees, saturation and lightness, the inverse of `hsl` ignoring alpha
+    pub fn to_hsl(self) -> [f32; 3] {
+        let [hue, min, max, chroma] = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = match lightness {
+            l if l <= 0.0 || l >= 1.0 => 0.0,
+            l => chroma / (1.0 - (2.0 * l - 1.0).abs()),
+        };
+        [hue, saturation, lightness]
+    }
+    /// hue in degrees, saturation and value, the inverse of `hsv` ignoring alpha
+    pub fn to_hsv(self) -> [f32; 3] {
+        let [hue, _, max, chroma] = self.hue();
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        [hue, saturation, max]
+    }
+    /// linear rgb and alpha from 0 to 1, like the renderer blends them
+    pub fn to_linear(self) -> [f32; 4] {
+        let [r, g, b, a] = [self.r, self.g, self.b, self.a].map(|c| c as f32 / 255.0);
+        [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+    }
+    /// the inverse of `to_linear`, channels are clamped to 0..=1
+    pub fn from_linear([r, g, b, a]: [f32; 4]) -> Self {
+        let [r, g, b] = [r, g, b].map(|c| unorm(linear_to_srgb(c.clamp(0.0, 1.0))));
+        Self::rgba(r, g, b, unorm(a))
+    }
+    /// mixes `self` and `other` in linear space, `t` of 0 is `self` and 1 is `other`
+    pub fn mix(self, other: Self, t: f32) -> Self {
+        let [a, b] = [self.to_linear(), other.to_linear()];
+        Self::from_linear([0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t))
+    }
+}
+
+/// a css color name or a hex color like `hex` takes it, with or without the `#`
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            return Self::hex(s);
+        }
+        if let Some(color) = Self::named(s) {
+            return Ok(color);
+        }
+        // no css name is only hex digits, so these can't be a misspelled name
+        match !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit()) {
+            true => Self::hex(s),
+            false => Err(ColorError::UnknownName(s.to_string())),
+        }
+    }
+}
+
+impl From<[u8; 4]> for Color {
+    fn from([r, g, b, a]: [u8; 4]) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<Color> for [u8; 4] {
+    fn from(c: Color) -> Self {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_forms() {
+        let c = Color::rgba(0x11, 0x22, 0x33, 0x44);
+        assert_eq!(Color::hex("#1234"), Ok(c));
+        assert_eq!(Color::hex("11223344"), Ok(c));
+        assert_eq!(Color::hex("#112233"), Ok(c.with_alpha(255)));
+        assert_eq!(Color::hex("123"), Ok(c.with_alpha(255)));
+        assert_eq!(Color::hex("#12345"), Err(ColorError::InvalidLength(5)));
+        assert_eq!(Color::hex("#12g"), Err(ColorError::InvalidDigit('g')));
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Color::named("RebeccaPurple"), Some(Color::rgb(0x66, 0x33, 0x99)));
+        assert_eq!(Color::named("transparent"), Some(Color::TRANSPARENT));
+        assert_eq!(Color::named("blurple"), None);
+    }
+
+    #[test]
+    fn parses_names_and_hex_with_or_without_hash() {
+        let red = Ok(Color::rgb(255, 0, 0));
+        assert_eq!("red".parse(), red);
+        assert_eq!(" #ff0000 ".parse(), red);
+        assert_eq!("ff0000".parse(), red);
+        assert_eq!("f00".parse(), red);
+        assert_eq!("ff000".parse::<Color>(), Err(ColorError::InvalidLength(5)));
+        let unknown = ColorError::UnknownName("blurple".into());
+        assert_eq!("blurple".parse::<Color>(), Err(unknown));
+        assert_eq!("".parse::<Color>(), Err(ColorError::UnknownName("".into())));
+    }
+
+    #[test]
+    fn hsl_and_hsv_round_trip() {
+        for (color, hsl, hsv) in [
+            (Color::rgb(255, 0, 0), [0.0, 1.0, 0.5], [0.0, 1.0, 1.0]),
+            (Color::rgb(0, 128, 0), [120.0, 1.0, 0.251], [120.0, 1.0, 0.502]),
+            (Color::rgb(0, 0, 255), [240.0, 1.0, 0.5], [240.0, 1.0, 1.0]),
+            (Color::rgb(128, 128, 128), [0.0, 0.0, 0.502], [0.0, 0.0, 0.502]),
+        ] {
+            let close = |a: [f32; 3], b: [f32; 3]| (0..3).all(|i| (a[i] - b[i]).abs() < 1e-3);
+            assert!(close(color.to_hsl(), hsl), "{color:?} {:?}", color.to_hsl());
+            assert!(close(color.to_hsv(), hsv), "{color:?} {:?}", color.to_hsv());
+            assert_eq!(Color::hsl(hsl[0], hsl[1], hsl[2]), color);
+            assert_eq!(Color::hsv(hsv[0], hsv[1], hsv[2]), color);
+        }
+        // every 8 bit color survives the round trip
+        for color in [Color::rgb(12, 200, 77), Color::rgb(250, 128, 114), Color::WHITE] {
+            let [h, s, l] = color.to_hsl();
+            assert_eq!(Color::hsl(h, s, l), color);
+            let [h, s, v] = color.to_hsv();
+            assert_eq!(Color::hsv(h, s, v), color);
+        }
+        // hues wrap around
+        assert_eq!(Color::hsl(-240.0, 1.0, 0.5), Color::hsl(120.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn linear_round_trip() {
+        for v in 0..=255 {
+            let color = Color::rgba(v, 255 - v, v / 2, v);
+            assert_eq!(Color::from_linear(color.to_linear()), color);
+        }
+        let [r, g, b, a] = Color::rgba(255, 128, 0, 51).to_linear();
+        assert_eq!([r, b], [1.0, 0.0]);
+        assert!((g - 0.2158).abs() < 1e-3);
+        assert!((a - 0.2).abs() < 1e-6);
+        assert_eq!(Color::from_linear([2.0, -1.0, 0.5, 1.0]), Color::rgb(255, 0, 188));
+    }
+
+    #[test]
+    fn mix_in_linear_space() {
+        let [black, white] = [Color::BLACK, Color::WHITE];
+        assert_eq!(black.mix(white, 0.0), black);
+        assert_eq!(black.mix(white, 1.0), white);
+        // halfway in linear light is brighter than the srgb midpoint
+        assert_eq!(black.mix(white, 0.5), Color::rgb(188, 188, 188));
+        let red = Color::rgb(255, 0, 0);
+        assert_eq!(red.mix(Color::TRANSPARENT, 0.5), Color::rgba(188, 0, 0, 128));
+    }
+}
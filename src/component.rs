@@ -1,16 +1,27 @@
 use std::{
     cell::RefCell,
     fmt::Debug,
-    sync::{mpsc, Arc},
+    fs,
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
 };
 
+use ab_glyph::FontArc;
+
 use crate::{
+    color::Color,
+    error::{FontError, ImageError, LayoutError},
     events::{KeyboardEvent, MouseEvent},
+    floating::Anchor,
     frame::FrameHandle,
     grid::{GridBuilder, GridHandle, XName, YName},
-    handle::HandleLike,
-    manager::{BBox, Borders, Rect},
+    handle::{FallableHandleLike, HandleLike},
+    images::{ImageHandle, ImageMessage},
+    manager::{Borders, Rect},
     render_actor::{FrameMessage, UpdateMessage},
+    scroll::{Scroll, ScrollExtents},
+    text::{FontHandle, TextMessage},
+    units::UserUnits,
     update_queue::{self, back::Update, front},
     EventDispatcher, Subscriber, UpdateMsg,
 };
@@ -18,6 +29,7 @@ use crate::{
 pub struct SystemEvents {
     mouse_dispatcher: EventDispatcher<MouseEvent>,
     keyboard_dispatcher: EventDispatcher<KeyboardEvent>,
+    error_dispatcher: EventDispatcher<LayoutError>,
 }
 impl SystemEvents {
     pub fn add_mouse_observer<C: State + Subscriber<MouseEvent> + 'static>(
@@ -32,11 +44,21 @@ impl SystemEvents {
     ) {
         self.keyboard_dispatcher.register(component)
     }
+    /// layout errors that can only be found once the frame is placed, like a full grid
+    pub fn add_error_observer<C: State + Subscriber<LayoutError> + 'static>(
+        &mut self,
+        component: &Component<C>,
+    ) {
+        self.error_dispatcher.register(component)
+    }
 }
 pub struct ComponentBuilder {
     render_sender: mpsc::Sender<UpdateMessage>,
     frame_count: usize,
     grid_count: usize,
+    font_count: usize,
+    image_count: usize,
+    grid_tracks: Vec<[Option<usize>; 2]>,
     dispatcher: SystemEvents,
     queue: front::UpdateQueue,
 }
@@ -59,9 +81,13 @@ impl ComponentBuilder {
             render_sender: send,
             frame_count: 0,
             grid_count: 0,
+            font_count: 0,
+            image_count: 0,
+            grid_tracks: vec![],
             dispatcher: SystemEvents {
                 mouse_dispatcher: EventDispatcher::new(&queue),
                 keyboard_dispatcher: EventDispatcher::new(&queue),
+                error_dispatcher: EventDispatcher::new(&queue),
             },
             queue,
         }
@@ -81,7 +107,8 @@ impl ComponentBuilder {
                 FrameMessage {
                     size: None,
                     margin: None,
-                    color: Some([255; 4]),
+                    color: Some(Color::WHITE),
+                    ..FrameMessage::default()
                 },
                 res,
             ))
@@ -89,19 +116,45 @@ impl ComponentBuilder {
         self.frame_count += 1;
         return res;
     }
-    pub fn send_floating(&mut self, size: BBox) -> FrameHandle {
+    pub fn send_floating(&mut self, size: Rect<UserUnits>) -> FrameHandle {
         self.render_sender
-            .send(UpdateMessage::NewFloatingFrame(FrameMessage {
-                size: Some(size.into()),
-                margin: None,
-                color: Some([255; 4]),
-            }))
+            .send(UpdateMessage::NewFloatingFrame(
+                size,
+                FrameMessage {
+                    margin: None,
+                    color: Some(Color::WHITE),
+                    ..FrameMessage::default()
+                },
+            ))
             .unwrap();
         let res = FrameHandle::new(self.frame_count);
         self.frame_count += 1;
         return res;
     }
+    pub fn send_anchored(
+        &mut self,
+        target: FrameHandle,
+        anchor: Anchor,
+        size: Rect<UserUnits>,
+    ) -> FrameHandle {
+        self.render_sender
+            .send(UpdateMessage::NewAnchoredFrame(
+                target,
+                anchor,
+                size,
+                FrameMessage {
+                    margin: None,
+                    color: Some(Color::WHITE),
+                    ..FrameMessage::default()
+                },
+            ))
+            .unwrap();
+        let res = FrameHandle::new(self.frame_count);
+        self.frame_count += 1;
+        res
+    }
     pub fn send_grid(&mut self, grid: GridBuilder) -> GridHandle {
+        self.grid_tracks.push(grid.track_limits());
         self.render_sender
             .send(UpdateMessage::NewGrid(
                 GridHandle::new(self.grid_count),
@@ -112,9 +165,43 @@ impl ComponentBuilder {
         self.grid_count += 1;
         return res;
     }
-    pub(crate) fn send_app<App: State<Param = ()>>(&mut self, size: BBox) -> Component<App> {
+    pub fn send_scroll(&mut self, grid: GridBuilder) -> Scroll {
+        let res = self.send_grid(grid);
+        let extents = Arc::new(Mutex::new(ScrollExtents::default()));
+        self.render_sender
+            .send(UpdateMessage::NewScroll(res, extents.clone()))
+            .unwrap();
+        Scroll::new(res, extents, self.render_sender.clone())
+    }
+    pub fn send_font(&mut self, font: FontArc) -> FontHandle {
+        let res = FontHandle::new(self.font_count);
+        self.render_sender
+            .send(UpdateMessage::NewFont(res, font))
+            .unwrap();
+        self.font_count += 1;
+        res
+    }
+    pub fn send_text(&mut self, frame: FrameHandle, text: TextMessage) {
+        self.render_sender
+            .send(UpdateMessage::ModifyText(frame, text))
+            .unwrap();
+    }
+    pub fn send_image(&mut self, image: image::RgbaImage) -> ImageHandle {
+        let res = ImageHandle::new(self.image_count);
+        self.render_sender
+            .send(UpdateMessage::NewImage(res, image))
+            .unwrap();
+        self.image_count += 1;
+        res
+    }
+    pub fn send_frame_image(&mut self, frame: FrameHandle, image: ImageMessage) {
+        self.render_sender
+            .send(UpdateMessage::ModifyImage(frame, image))
+            .unwrap();
+    }
+    pub(crate) fn send_app<App: State<Param = ()>>(&mut self, size: Rect<i32>) -> Component<App> {
         assert!(self.frame_count == 0);
-        let res = self.send_floating(size);
+        let res = self.send_floating(size.units());
         let mut b = Builder::first(self);
         let app = Component::new(App::init(&mut b, &()), ComponentType::Floating(res));
         App::after_init(&app, &mut self.dispatcher, &());
@@ -123,6 +210,30 @@ impl ComponentBuilder {
     pub(crate) fn emit_mouse(&self, event: MouseEvent) {
         self.dispatcher.mouse_dispatcher.emit(event)
     }
+    pub(crate) fn emit_error(&self, event: LayoutError) {
+        self.dispatcher.error_dispatcher.emit(event)
+    }
+    /// the checks that don't need the grid's current placements
+    fn check_frame(
+        &self,
+        grid: GridHandle,
+        x: Option<XName>,
+        y: Option<YName>,
+    ) -> Result<(), LayoutError> {
+        let grid = grid.index();
+        let Some(&[x_len, y_len]) = self.grid_tracks.get(grid) else {
+            return Err(LayoutError::UnknownGrid { grid });
+        };
+        match (x.index(), y.index(), x_len, y_len) {
+            (Some(x), _, Some(len), _) if x >= len => {
+                Err(LayoutError::XOutOfRange { grid, x, len })
+            }
+            (_, Some(y), _, Some(len)) if y >= len => {
+                Err(LayoutError::YOutOfRange { grid, y, len })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 impl<'a> Builder<'a, ()> {
     pub(crate) fn first(b: &'a mut ComponentBuilder) -> Self {
@@ -140,12 +251,34 @@ impl<'a, C: State> Builder<'a, C> {
             parent: component,
         }
     }
+    /// frames that can't be placed are reported to `SystemEvents::add_error_observer`,
+    /// see `try_frame` to check them here
     pub fn frame<T: State>(
         &mut self,
         param: T::Param,
         grid: GridHandle,
         x: Option<XName>,
         y: Option<YName>,
+    ) -> Component<T> {
+        self.grid_member(param, grid, x, y)
+    }
+    /// errors found after placement, like a full grid, go to `SystemEvents::add_error_observer`
+    pub fn try_frame<T: State>(
+        &mut self,
+        param: T::Param,
+        grid: GridHandle,
+        x: Option<XName>,
+        y: Option<YName>,
+    ) -> Result<Component<T>, LayoutError> {
+        self.b.check_frame(grid, x, y)?;
+        Ok(self.grid_member(param, grid, x, y))
+    }
+    fn grid_member<T: State>(
+        &mut self,
+        param: T::Param,
+        grid: GridHandle,
+        x: Option<XName>,
+        y: Option<YName>,
     ) -> Component<T> {
         let res = self.b.send_frame(grid, x, y);
         let me = Component::new(
@@ -156,10 +289,28 @@ impl<'a, C: State> Builder<'a, C> {
             ComponentType::GridMember(res, grid),
         );
         T::after_init(&me, &mut self.b.dispatcher, &param);
-        return me;
+        me
     }
-    pub fn floating_frame<T: State>(&mut self, param: T::Param, size: Rect<i32>) -> Component<T> {
-        let res = self.b.send_floating(size.into());
+    /// `size` is resolved against the window whenever it is resized
+    pub fn floating_frame<T: State, U: Into<UserUnits>>(
+        &mut self,
+        param: T::Param,
+        size: Rect<U>,
+    ) -> Component<T> {
+        let res = self.b.send_floating(size.units());
+        Component::new(T::init(self, &param), ComponentType::Floating(res))
+    }
+    /// `size.x` and `size.y` offset the frame away from the anchor's edge
+    pub fn anchored_frame<T: State, A: State, U: Into<UserUnits>>(
+        &mut self,
+        param: T::Param,
+        target: &Component<A>,
+        anchor: Anchor,
+        size: Rect<U>,
+    ) -> Component<T> {
+        let res = self
+            .b
+            .send_anchored(target.handle.frame(), anchor, size.units());
         Component::new(T::init(self, &param), ComponentType::Floating(res))
     }
     pub fn grid_builder(&mut self) -> GridBuilder {
@@ -173,9 +324,38 @@ impl<'a, C: State> Builder<'a, C> {
         let res = self.b.send_grid(grid);
         return res;
     }
+    /// the grid's content keeps its natural size and scrolls inside of the parent frame
+    pub fn scroll_grid(&mut self, grid: GridBuilder) -> (GridHandle, Scroll) {
+        let res = self.b.send_scroll(grid);
+        (res.grid(), res)
+    }
     pub fn event_dispatcher<Event>(&self) -> EventDispatcher<Event> {
         EventDispatcher::new(&self.b.queue)
     }
+    /// parses a ttf or otf font, the first font loaded is used by text without a font
+    pub fn font(&mut self, data: Vec<u8>) -> Result<FontHandle, FontError> {
+        let font = FontArc::try_from_vec(data)?;
+        Ok(self.b.send_font(font))
+    }
+    pub fn font_file(&mut self, path: impl AsRef<Path>) -> Result<FontHandle, FontError> {
+        self.font(fs::read(path)?)
+    }
+    /// sets the text of a component built by this builder, later changes go through `UpdateMsg::Text`
+    pub fn text<T: State>(&mut self, component: &Component<T>, text: TextMessage) {
+        self.b.send_text(component.handle.frame(), text);
+    }
+    /// decodes a png or jpeg image, it is uploaded to the gpu once and can be shown by any frame
+    pub fn image(&mut self, data: &[u8]) -> Result<ImageHandle, ImageError> {
+        let image = image::load_from_memory(data)?.to_rgba8();
+        Ok(self.b.send_image(image))
+    }
+    pub fn image_file(&mut self, path: impl AsRef<Path>) -> Result<ImageHandle, ImageError> {
+        self.image(&fs::read(path)?)
+    }
+    /// sets the image of a component built by this builder, later changes go through `UpdateMsg::Image`
+    pub fn set_image<T: State>(&mut self, component: &Component<T>, image: ImageMessage) {
+        self.b.send_frame_image(component.handle.frame(), image);
+    }
 }
 
 pub struct UpdateQueue<'a> {
@@ -0,0 +1,126 @@
+use bytemuck::Zeroable;
+
+use crate::{
+    color::Color,
+    frame::{FrameData, FrameStore},
+    grid::GridHandle,
+    handle::HandleLike,
+    layout::Layout,
+    manager::BBox,
+    units::VUnit,
+};
+
+const TRACK_COLOR: Color = Color::rgba(0, 200, 255, 160);
+const FRAME_COLOR: Color = Color::rgb(255, 0, 255);
+const MARGIN_COLOR: Color = Color::rgb(255, 200, 0);
+const LABEL_COLOR: Color = Color::rgba(0, 0, 0, 180);
+const DIGIT_COLOR: Color = Color::WHITE;
+
+/// size of one font pixel in logical pixels
+const DIGIT_SCALE: i32 = 2;
+/// 3x5 bitmaps of the digits, one row per 3 bits with the top row in the high bits
+const DIGITS: [u16; 10] = [
+    0b111_101_101_101_111,
+    0b010_110_010_010_111,
+    0b111_001_111_100_111,
+    0b111_001_111_001_111,
+    0b101_101_111_001_001,
+    0b111_100_111_001_111,
+    0b111_100_111_101_111,
+    0b111_001_010_010_010,
+    0b111_101_111_101_111,
+    0b111_101_111_001_111,
+];
+
+/// quads drawn over the ui showing grid cells, frame bounds, margins and frame indices
+#[derive(Default)]
+pub struct DebugOverlay {
+    enabled: bool,
+    quads: Vec<FrameData>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// `None` toggles the overlay
+    pub fn set(&mut self, enabled: Option<bool>) {
+        self.enabled = enabled.unwrap_or(!self.enabled);
+    }
+    pub fn quads(&self) -> &[FrameData] {
+        &self.quads
+    }
+    /// clipped to `clip` so content scrolled out of view is not outlined
+    fn push(&mut self, bounds: BBox, clip: &BBox, color: Color) {
+        let data = bounds.intersect(clip);
+        if data.w > 0.into() && data.h > 0.into() {
+            self.quads.push(FrameData {
+                data,
+                color,
+                camera_index: FrameStore::WINDOW_CAMERA,
+                opacity: [1.0; 2],
+                ..FrameData::zeroed()
+            });
+        }
+    }
+    fn outline(&mut self, b: BBox, clip: &BBox, color: Color) {
+        let px: VUnit = 1.into();
+        let BBox { x, y, w, h } = b;
+        self.push(BBox { x, y, w, h: px }, clip, color);
+        self.push(BBox { x, y: y + h - px, w, h: px }, clip, color);
+        self.push(BBox { x, y, w: px, h }, clip, color);
+        self.push(BBox { x: x + w - px, y, w: px, h }, clip, color);
+    }
+    fn label(&mut self, index: usize, x: VUnit, y: VUnit, clip: &BBox) {
+        let digits = index.to_string();
+        let pad: VUnit = DIGIT_SCALE.into();
+        let advance = 4 * DIGIT_SCALE;
+        let w: VUnit = (digits.len() as i32 * advance + DIGIT_SCALE).into();
+        let h: VUnit = (7 * DIGIT_SCALE).into();
+        self.push(BBox { x, y, w, h }, clip, LABEL_COLOR);
+        for (i, d) in digits.bytes().enumerate() {
+            let bits = DIGITS[(d - b'0') as usize];
+            let left = x + pad + (i as i32 * advance).into();
+            for row in 0..5 {
+                let top = y + pad + (row * DIGIT_SCALE).into();
+                for col in 0..3 {
+                    if bits >> (14 - row * 3 - col) & 1 == 1 {
+                        let bounds = BBox {
+                            x: left + (col * DIGIT_SCALE).into(),
+                            y: top,
+                            w: pad,
+                            h: pad,
+                        };
+                        self.push(bounds, clip, DIGIT_COLOR);
+                    }
+                }
+            }
+        }
+    }
+    /// must run after the layout is solved
+    pub fn prepare(&mut self, layout: &Layout) {
+        self.quads.clear();
+        if !self.enabled {
+            return;
+        }
+        let frames = &layout.frames;
+        for grid in 0..layout.grids.len() {
+            let data = layout.grids.get(GridHandle::new(grid));
+            let camera = FrameStore::camera(data.parent().index());
+            let clip = frames.clip_rect(camera);
+            for cell in data.solved_cells() {
+                self.outline(frames.to_screen(camera, *cell), &clip, TRACK_COLOR);
+            }
+        }
+        for (index, f) in frames.data().iter().enumerate() {
+            let clip = frames.clip_rect(f.camera_index);
+            let visible = frames.screen_bounds(index);
+            let bounds = frames.to_screen(f.camera_index, f.data);
+            self.outline(bounds, &clip, FRAME_COLOR);
+            if visible.w != bounds.w || visible.h != bounds.h {
+                self.outline(visible, &clip, MARGIN_COLOR);
+            }
+            self.label(index, visible.x, visible.y, &clip);
+        }
+    }
+}
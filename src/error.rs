@@ -0,0 +1,240 @@
+use std::{error::Error, fmt};
+
+/// reasons a frame could not be placed in a grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    /// every cell of a grid without an expanding direction is taken
+    GridFull {
+        grid: usize,
+    },
+    /// the grid has no tracks to place a frame in
+    EmptyGrid {
+        grid: usize,
+    },
+    XOutOfRange {
+        grid: usize,
+        x: usize,
+        len: usize,
+    },
+    YOutOfRange {
+        grid: usize,
+        y: usize,
+        len: usize,
+    },
+    UnknownGrid {
+        grid: usize,
+    },
+    /// the breakpoint that just became active has no cell for the frame,
+    /// it is hidden until one with room for it is active again
+    Hidden {
+        grid: usize,
+        frame: usize,
+    },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GridFull { grid } => write!(f, "grid {grid} has no free cells"),
+            Self::EmptyGrid { grid } => write!(f, "grid {grid} has no tracks"),
+            Self::XOutOfRange { grid, x, len } => {
+                write!(
+                    f,
+                    "x {x} is out of range for grid {grid} with {len} columns"
+                )
+            }
+            Self::YOutOfRange { grid, y, len } => {
+                write!(f, "y {y} is out of range for grid {grid} with {len} rows")
+            }
+            Self::UnknownGrid { grid } => write!(f, "grid {grid} does not exist"),
+            Self::Hidden { grid, frame } => {
+                write!(f, "frame {frame} has no cell at the active breakpoint of grid {grid}")
+            }
+        }
+    }
+}
+
+impl Error for LayoutError {}
+
+/// a grid track template that could not be parsed, `pos` is the byte offset into the template
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TemplateError {
+    pub pos: usize,
+    pub kind: TemplateErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemplateErrorKind {
+    Empty,
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    /// not a number, or a fraction or pixel count that is not a whole number
+    InvalidNumber,
+    /// a number without a known unit like `px`, `fr` or `%`, only `0` may omit it
+    UnknownUnit,
+    ZeroRepeat,
+    /// a grid direction expands along at most one track
+    MultipleAutoFill,
+    /// `repeat(auto-fill, ...)` with more than one track, `pos` is the second one
+    AutoFillTracks,
+    /// the grid already expands along the other direction
+    ExpandsBothDirections,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pos = self.pos;
+        match self.kind {
+            TemplateErrorKind::Empty => write!(f, "grid template has no tracks"),
+            TemplateErrorKind::UnexpectedChar(c) => write!(f, "unexpected {c:?} at {pos}"),
+            TemplateErrorKind::UnexpectedEnd => write!(f, "unexpected end of template at {pos}"),
+            TemplateErrorKind::InvalidNumber => write!(f, "invalid number at {pos}"),
+            TemplateErrorKind::UnknownUnit => {
+                write!(f, "expected `px`, `fr`, `%`, `vw`, `vh` or `rem` at {pos}")
+            }
+            TemplateErrorKind::ZeroRepeat => write!(f, "repeat count at {pos} must not be 0"),
+            TemplateErrorKind::MultipleAutoFill => {
+                write!(f, "only a single auto-fill track is allowed, found another at {pos}")
+            }
+            TemplateErrorKind::AutoFillTracks => {
+                write!(f, "auto-fill repeats a single track, found another at {pos}")
+            }
+            TemplateErrorKind::ExpandsBothDirections => {
+                write!(f, "auto-fill at {pos} but the grid already expands the other way")
+            }
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+/// reasons an offscreen render could not be produced
+#[derive(Debug)]
+pub enum HeadlessError {
+    /// no adapter, not even a software one, is available
+    NoAdapter,
+    /// a side is 0 or larger than the adapter's biggest texture
+    InvalidSize {
+        width: u32,
+        height: u32,
+    },
+    RequestDevice(wgpu::RequestDeviceError),
+    BufferMap(wgpu::BufferAsyncError),
+    Encode(png::EncodingError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAdapter => write!(f, "no wgpu adapter is available"),
+            Self::InvalidSize { width, height } => {
+                write!(f, "can't render a {width}x{height} image")
+            }
+            Self::RequestDevice(e) => write!(f, "could not create a device: {e}"),
+            Self::BufferMap(e) => write!(f, "could not read back the render: {e}"),
+            Self::Encode(e) => write!(f, "could not encode the png: {e}"),
+            Self::Io(e) => write!(f, "could not write the png: {e}"),
+        }
+    }
+}
+
+impl Error for HeadlessError {}
+
+impl From<png::EncodingError> for HeadlessError {
+    fn from(e: png::EncodingError) -> Self {
+        Self::Encode(e)
+    }
+}
+
+impl From<std::io::Error> for HeadlessError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// reasons a font could not be loaded
+#[derive(Debug)]
+pub enum FontError {
+    /// not a ttf or otf font
+    Invalid(ab_glyph::InvalidFont),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "could not parse the font: {e}"),
+            Self::Io(e) => write!(f, "could not read the font: {e}"),
+        }
+    }
+}
+
+impl Error for FontError {}
+
+impl From<ab_glyph::InvalidFont> for FontError {
+    fn from(e: ab_glyph::InvalidFont) -> Self {
+        Self::Invalid(e)
+    }
+}
+
+impl From<std::io::Error> for FontError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// reasons an image could not be loaded
+#[derive(Debug)]
+pub enum ImageError {
+    /// not a png or jpeg image, or a corrupt one
+    Decode(image::ImageError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(e) => write!(f, "could not decode the image: {e}"),
+            Self::Io(e) => write!(f, "could not read the image: {e}"),
+        }
+    }
+}
+
+impl Error for ImageError {}
+
+impl From<image::ImageError> for ImageError {
+    fn from(e: image::ImageError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+impl From<std::io::Error> for ImageError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// a color that could not be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColorError {
+    /// hex colors have 3, 4, 6 or 8 digits
+    InvalidLength(usize),
+    InvalidDigit(char),
+    /// neither a hex color nor a css color name
+    UnknownName(String),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(len) => {
+                write!(f, "hex colors have 3, 4, 6 or 8 digits, found {len}")
+            }
+            Self::InvalidDigit(c) => write!(f, "{c:?} is not a hex digit"),
+            Self::UnknownName(name) => write!(f, "{name:?} is not a color"),
+        }
+    }
+}
+
+impl Error for ColorError {}
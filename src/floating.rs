@@ -0,0 +1,238 @@
+use crate::{
+    frame::{FrameHandle, FrameStore},
+    handle::HandleLike,
+    manager::{BBox, Rect},
+    units::{UserUnits, VUnit},
+};
+
+/// which side of the anchor frame a floating frame is placed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Above,
+    Below,
+    Left,
+    Right,
+    Inside,
+}
+
+impl Side {
+    fn flipped(self) -> Self {
+        match self {
+            Self::Above => Self::Below,
+            Self::Below => Self::Above,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Inside => Self::Inside,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    /// position of a span of `len` aligned inside of `[pos, pos + space)`
+    pub(crate) fn place(self, pos: VUnit, space: VUnit, len: VUnit) -> VUnit {
+        match self {
+            Self::Start => pos,
+            Self::Center => pos + (space - len) / 2,
+            Self::End => pos + space - len,
+        }
+    }
+    /// `Start` and `End` swap sides for right to left frames
+    pub(crate) fn mirrored(self, rtl: bool) -> Self {
+        match (self, rtl) {
+            (Self::Start, true) => Self::End,
+            (Self::End, true) => Self::Start,
+            (align, _) => align,
+        }
+    }
+}
+
+/// `align` positions the frame along the anchor's edge, for `Side::Inside` it is used on both axes
+/// `Start` along a horizontal edge is the right end when the anchor is right to left
+#[derive(Clone, Copy, Debug)]
+pub struct Anchor {
+    pub side: Side,
+    pub align: Align,
+}
+
+impl Anchor {
+    /// `size` holds the frame's dimensions and its offset from the anchored position,
+    /// along a horizontal edge `rtl` mirrors the alignment and the offset
+    fn bounds(&self, side: Side, target: &BBox, size: &BBox, rtl: bool) -> BBox {
+        let BBox { x: ox, y: oy, w, h } = *size;
+        let along_x = match rtl {
+            true => self.align.mirrored(true).place(target.x, target.w, w) - ox,
+            false => self.align.place(target.x, target.w, w) + ox,
+        };
+        let along_y = self.align.place(target.y, target.h, h) + oy;
+        let (x, y) = match side {
+            Side::Above => (along_x, target.y - h - oy),
+            Side::Below => (along_x, target.y + target.h + oy),
+            Side::Left => (target.x - w - ox, along_y),
+            Side::Right => (target.x + target.w + ox, along_y),
+            Side::Inside => (along_x, along_y),
+        };
+        BBox { x, y, w, h }
+    }
+    fn overflows(side: Side, b: &BBox, window: &BBox) -> bool {
+        match side {
+            Side::Above | Side::Below => b.y < window.y || b.y + b.h > window.y + window.h,
+            Side::Left | Side::Right => b.x < window.x || b.x + b.w > window.x + window.w,
+            Side::Inside => false,
+        }
+    }
+    pub(crate) fn place(&self, target: &BBox, size: &BBox, window: &BBox, rtl: bool) -> BBox {
+        let mut res = self.bounds(self.side, target, size, rtl);
+        if Self::overflows(self.side, &res, window) {
+            let flipped = self.bounds(self.side.flipped(), target, size, rtl);
+            if !Self::overflows(self.side.flipped(), &flipped, window) {
+                res = flipped;
+            }
+        }
+        // keep the frame on screen along the anchor's edge
+        let clamp = |pos: VUnit, len: VUnit, start: VUnit, space: VUnit| {
+            pos.min(start + space - len).max(start)
+        };
+        match self.side {
+            Side::Above | Side::Below => res.x = clamp(res.x, res.w, window.x, window.w),
+            Side::Left | Side::Right => res.y = clamp(res.y, res.h, window.y, window.h),
+            Side::Inside => (),
+        }
+        res
+    }
+}
+
+struct AnchoredFrame {
+    handle: FrameHandle,
+    target: FrameHandle,
+    anchor: Anchor,
+    size: Rect<UserUnits>,
+}
+
+#[derive(Default)]
+pub struct FloatingRenderer {
+    floating: Vec<(FrameHandle, Rect<UserUnits>)>,
+    anchored: Vec<AnchoredFrame>,
+}
+
+impl FloatingRenderer {
+    pub fn new() -> Self {
+        Self {
+            floating: vec![],
+            anchored: vec![],
+        }
+    }
+    pub fn add(&mut self, handle: FrameHandle, size: Rect<UserUnits>) {
+        self.floating.push((handle, size));
+    }
+    pub fn add_anchored(
+        &mut self,
+        handle: FrameHandle,
+        target: FrameHandle,
+        anchor: Anchor,
+        size: Rect<UserUnits>,
+    ) {
+        self.anchored.push(AnchoredFrame {
+            handle,
+            target,
+            anchor,
+            size,
+        });
+    }
+    /// must run after the grids are solved so anchors follow layout changes
+    pub fn prepare(&mut self, frame_renderer: &mut FrameStore) {
+        let window = frame_renderer.get(0).data;
+        let ctx = frame_renderer.unit_context();
+        for (handle, size) in &self.floating {
+            frame_renderer.update(handle.index(), &size.solve(&window, &ctx));
+        }
+        for f in &self.anchored {
+            let target = frame_renderer.screen_bounds(f.target.index());
+            // the offset is relative to the anchor's edge, not the window's origin
+            let origin = BBox {
+                x: 0.into(),
+                y: 0.into(),
+                ..window
+            };
+            let size = f.size.solve(&origin, &ctx);
+            let rtl = frame_renderer.is_rtl(f.target.index());
+            let bounds = f.anchor.place(&target, &size, &window, rtl);
+            frame_renderer.update(f.handle.index(), &bounds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x: i32, y: i32, w: i32, h: i32) -> BBox {
+        BBox {
+            x: x.into(),
+            y: y.into(),
+            w: w.into(),
+            h: h.into(),
+        }
+    }
+    fn pix(b: BBox) -> [f32; 4] {
+        [b.x, b.y, b.w, b.h].map(|v| v.pix())
+    }
+    const BELOW: Anchor = Anchor {
+        side: Side::Below,
+        align: Align::Start,
+    };
+
+    #[test]
+    fn fits_below() {
+        let window = bbox(0, 0, 200, 100);
+        let placed = BELOW.place(&bbox(50, 20, 40, 10), &bbox(0, 4, 30, 20), &window, false);
+        assert_eq!(pix(placed), [50.0, 34.0, 30.0, 20.0]);
+    }
+
+    #[test]
+    fn flips_above_when_below_overflows() {
+        let window = bbox(0, 0, 200, 100);
+        let placed = BELOW.place(&bbox(50, 80, 40, 10), &bbox(0, 0, 30, 20), &window, false);
+        assert_eq!(pix(placed), [50.0, 60.0, 30.0, 20.0]);
+        // neither side fits, it stays on its own side
+        let placed = BELOW.place(&bbox(50, 40, 40, 10), &bbox(0, 0, 30, 60), &window, false);
+        assert_eq!(pix(placed), [50.0, 50.0, 30.0, 60.0]);
+    }
+
+    #[test]
+    fn clamps_along_the_edge() {
+        let window = bbox(0, 0, 200, 100);
+        let placed = BELOW.place(&bbox(190, 20, 10, 10), &bbox(0, 0, 30, 20), &window, false);
+        assert_eq!(pix(placed), [170.0, 30.0, 30.0, 20.0]);
+        let right = Anchor {
+            side: Side::Right,
+            align: Align::End,
+        };
+        // the end of the anchor is above the window, so it is pushed back down
+        let placed = right.place(&bbox(50, 0, 20, 10), &bbox(0, 0, 30, 20), &window, false);
+        assert_eq!(pix(placed), [70.0, 0.0, 30.0, 20.0]);
+    }
+
+    #[test]
+    fn right_to_left_mirrors_alignment_and_offset() {
+        let window = bbox(0, 0, 200, 100);
+        let target = bbox(50, 20, 40, 10);
+        // `Start` is the anchor's right end and the offset moves left
+        let placed = BELOW.place(&target, &bbox(10, 0, 30, 20), &window, true);
+        assert_eq!(pix(placed), [50.0, 30.0, 30.0, 20.0]);
+        let placed = BELOW.place(&target, &bbox(10, 0, 30, 20), &window, false);
+        assert_eq!(pix(placed), [60.0, 30.0, 30.0, 20.0]);
+        let center = Anchor {
+            side: Side::Below,
+            align: Align::Center,
+        };
+        let placed = center.place(&target, &bbox(0, 0, 30, 20), &window, true);
+        assert_eq!(pix(placed), [55.0, 30.0, 30.0, 20.0]);
+    }
+}
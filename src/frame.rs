@@ -1,5 +1,9 @@
 mod data;
+mod dirty;
 mod renderer;
+mod store;
 
-pub use data::FrameData;
-pub use renderer::{FrameHandle, FrameRenderer};
+pub use data::{AspectRatio, Border, ColorStop, FrameData, Gradient, Layer, Shadow};
+pub(crate) use renderer::grown;
+pub use renderer::{FrameContent, FrameHandle, FrameRenderer};
+pub use store::{ContentSpan, FrameStore};
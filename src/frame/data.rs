@@ -2,7 +2,12 @@ use std::mem;
 
 use bytemuck::{Pod, Zeroable};
 
-use crate::manager::{BBox, MarginBox};
+use crate::{
+    color::Color,
+    floating::Align,
+    manager::{BBox, MarginBox},
+    units::{UserUnits, VUnit},
+};
 
 #[repr(usize)]
 #[derive(Clone, Copy)]
@@ -13,11 +18,135 @@ pub enum Loc {
     H,
 }
 
-pub enum Border {
-    TOP,
-    BOTTOM,
-    LEFT,
-    RIGHT,
+/// stroke along one side of a frame, drawn inside of its visible bounds,
+/// `width` is resolved like the margin on the same side
+#[derive(Clone, Debug)]
+pub struct Border {
+    pub width: UserUnits,
+    pub color: Color,
+}
+
+/// shrinks a frame to `ratio` of width over height and aligns it on the axis with space left
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AspectRatio {
+    pub ratio: f32,
+    pub align: Align,
+}
+
+impl AspectRatio {
+    pub(crate) fn fit(&self, b: &BBox) -> BBox {
+        let BBox { x, y, w, h } = *b;
+        if self.ratio <= 0.0 || h <= 0.into() {
+            return *b;
+        }
+        let fitted_w: VUnit = (h.pix() * self.ratio).into();
+        match fitted_w < w {
+            true => BBox {
+                x: self.align.place(x, w, fitted_w),
+                w: fitted_w,
+                ..*b
+            },
+            false => {
+                let fitted_h: VUnit = (w.pix() / self.ratio).into();
+                BBox {
+                    y: self.align.place(y, h, fitted_h),
+                    h: fitted_h,
+                    ..*b
+                }
+            }
+        }
+    }
+}
+
+/// groups of frames drawn on top of each other in this order, children are in their parent's layer
+/// unless they set their own
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    #[default]
+    Base,
+    Popup,
+    Tooltip,
+    Overlay,
+}
+
+/// box shadow drawn behind a frame's visible bounds, it follows the frame's corner radii,
+/// `offset` is relative to the frame's width and height, `blur` and `spread` to its smaller side
+#[derive(Clone, Debug)]
+pub struct Shadow {
+    pub offset: [UserUnits; 2],
+    pub blur: UserUnits,
+    /// grows the shadow past the frame on every side
+    pub spread: UserUnits,
+    pub color: Color,
+}
+
+/// fill of a frame's visible bounds that replaces its color
+#[derive(Clone, Debug)]
+pub enum Gradient {
+    /// `angle` in degrees clockwise from pointing up like css, 180 runs from top to bottom
+    Linear { angle: f32, stops: Vec<ColorStop> },
+    /// `center` is relative to the visible bounds, an offset of 1 reaches the sides of a centered gradient
+    Radial { center: [f32; 2], stops: Vec<ColorStop> },
+}
+
+/// `offset` runs from 0 at the start of the gradient to 1 at its end
+#[derive(Clone, Copy, Debug)]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+impl Gradient {
+    const LINEAR: u32 = 1;
+    const RADIAL: u32 = 2;
+
+    pub fn stops(&self) -> &[ColorStop] {
+        match self {
+            Self::Linear { stops, .. } | Self::Radial { stops, .. } => stops,
+        }
+    }
+    /// appends the stops sorted by offset, `first` is their index in the stop buffer
+    pub(crate) fn data(&self, first: usize, stops: &mut Vec<StopData>) -> GradientData {
+        let mut sorted = self.stops().to_vec();
+        sorted.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        stops.extend(sorted.iter().map(|s| StopData {
+            color: s.color.to_linear(),
+            offset: s.offset,
+            _pad: [0.0; 3],
+        }));
+        let (kind, params) = match self {
+            Self::Linear { angle, .. } => (Self::LINEAR, [angle.to_radians(), 0.0, 0.0, 0.0]),
+            Self::Radial { center, .. } => (Self::RADIAL, [center[0], center[1], 0.0, 0.0]),
+        };
+        GradientData {
+            kind,
+            first: first as u32,
+            count: sorted.len() as u32,
+            _pad: 0,
+            params,
+        }
+    }
+}
+
+/// gpu side of a `Gradient`, read by the fragment shader from a storage buffer
+#[derive(Pod, Clone, Copy, Zeroable, Debug)]
+#[repr(C)]
+pub struct GradientData {
+    kind: u32,
+    first: u32,
+    count: u32,
+    _pad: u32,
+    /// the angle in radians of linear gradients, the center of radial ones
+    params: [f32; 4],
+}
+
+#[derive(Pod, Clone, Copy, Zeroable, Debug)]
+#[repr(C)]
+pub struct StopData {
+    /// linear, so stops are interpolated like everything else is blended
+    color: [f32; 4],
+    offset: f32,
+    _pad: [f32; 3],
 }
 
 #[derive(Pod, Clone, Copy, Zeroable, Debug)]
@@ -25,11 +154,28 @@ pub enum Border {
 pub struct FrameData {
     pub data: BBox,
     pub margin: MarginBox,
-    pub color: [u8; 4],
+    pub color: Color,
     pub camera_index: u32,
+    /// top left, top right, bottom right and bottom left
+    pub radius: [VUnit; 4],
+    pub border: MarginBox,
+    /// in the order of `border`
+    pub border_color: [Color; 4],
+    /// index into the gradient buffer plus one, 0 fills the frame with `color`
+    pub gradient: u32,
+    /// x and y offset, blur and spread of the shadow
+    pub shadow: [VUnit; 4],
+    /// a transparent shadow is not drawn
+    pub shadow_color: Color,
+    /// radius what is drawn behind the frame is blurred by, 0 draws nothing behind it
+    pub backdrop_blur: VUnit,
+    /// multiplies the alpha of the frame with its text and images, and of its opacity group
+    pub opacity: [f32; 2],
 }
 
 impl FrameData {
+    const BORDER_COLOR_OFFSET: u64 =
+        mem::size_of::<(BBox, MarginBox, [u8; 4], u32, [VUnit; 4], MarginBox)>() as u64;
     pub const fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
             array_stride: mem::size_of::<Self>() as u64,
@@ -55,8 +201,80 @@ impl FrameData {
                     offset: mem::size_of::<(BBox, MarginBox, [u8; 4])>() as u64,
                     shader_location: 4,
                 },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Sint32x4,
+                    offset: mem::size_of::<(BBox, MarginBox, [u8; 4], u32)>() as u64,
+                    shader_location: 5,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Sint32x4,
+                    offset: mem::size_of::<(BBox, MarginBox, [u8; 4], u32, [VUnit; 4])>() as u64,
+                    shader_location: 6,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Unorm8x4,
+                    offset: Self::BORDER_COLOR_OFFSET,
+                    shader_location: 7,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Unorm8x4,
+                    offset: Self::BORDER_COLOR_OFFSET + 4,
+                    shader_location: 8,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Unorm8x4,
+                    offset: Self::BORDER_COLOR_OFFSET + 8,
+                    shader_location: 9,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Unorm8x4,
+                    offset: Self::BORDER_COLOR_OFFSET + 12,
+                    shader_location: 10,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Uint32,
+                    offset: Self::BORDER_COLOR_OFFSET + 16,
+                    shader_location: 11,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Sint32x4,
+                    offset: Self::BORDER_COLOR_OFFSET + 20,
+                    shader_location: 12,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Unorm8x4,
+                    offset: Self::BORDER_COLOR_OFFSET + 36,
+                    shader_location: 13,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Sint32,
+                    offset: Self::BORDER_COLOR_OFFSET + 40,
+                    shader_location: 14,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x2,
+                    offset: Self::BORDER_COLOR_OFFSET + 44,
+                    shader_location: 15,
+                },
             ],
         }
     }
+    /// the frame's bounds after its margin is applied, as drawn by the shader
+    pub fn visible_bounds(&self) -> BBox {
+        let BBox { x, y, w, h } = self.data;
+        let MarginBox {
+            top,
+            bottom,
+            left,
+            right,
+        } = self.margin;
+        BBox {
+            x: x + left,
+            y: y + top,
+            w: (w - left - right).max(0.into()),
+            h: (h - top - bottom).max(0.into()),
+        }
+    }
+    /// starting size of the frame and overlay instance buffers, both grow past it
     pub const BUFFER_INIT_BYTE_COUNT: u64 = 10000 * mem::size_of::<Self>() as u64;
 }
@@ -0,0 +1,112 @@
+use std::{mem, ops::Range};
+
+/// indices changed since the last `take`, coalesced into a few ranges so scattered
+/// changes don't each need their own upload
+#[derive(Default)]
+pub struct DirtyRanges {
+    ranges: Vec<Range<usize>>,
+}
+
+impl DirtyRanges {
+    /// ranges at most this many indices apart are uploaded as one
+    const GAP: usize = 16;
+    /// the gap grows until there are no more ranges than this
+    const MAX_RANGES: usize = 64;
+
+    pub fn mark(&mut self, index: usize) {
+        self.mark_range(index..index + 1);
+    }
+    pub fn mark_range(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        // changes usually come in index order, so most extend the last range
+        if let Some(last) = self.ranges.last_mut() {
+            if last.start <= range.start && range.start <= last.end + Self::GAP {
+                last.end = last.end.max(range.end);
+                return;
+            }
+        }
+        self.ranges.push(range);
+        if self.ranges.len() > Self::MAX_RANGES * 2 {
+            self.coalesce();
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+    /// sorted ranges that don't overlap, oldest changes included
+    pub fn take(&mut self) -> Vec<Range<usize>> {
+        self.coalesce();
+        mem::take(&mut self.ranges)
+    }
+    fn coalesce(&mut self) {
+        self.ranges.sort_unstable_by_key(|r| r.start);
+        let mut gap = Self::GAP;
+        loop {
+            let mut merged: Vec<Range<usize>> = Vec::with_capacity(self.ranges.len());
+            for range in self.ranges.drain(..) {
+                match merged.last_mut() {
+                    Some(last) if range.start <= last.end + gap => {
+                        last.end = last.end.max(range.end)
+                    }
+                    _ => merged.push(range),
+                }
+            }
+            self.ranges = merged;
+            if self.ranges.len() <= Self::MAX_RANGES {
+                return;
+            }
+            gap *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marked(indices: impl IntoIterator<Item = usize>) -> Vec<Range<usize>> {
+        let mut dirty = DirtyRanges::default();
+        for index in indices {
+            dirty.mark(index);
+        }
+        dirty.take()
+    }
+
+    #[test]
+    fn adjacent_marks_merge() {
+        assert_eq!(marked([3, 4, 5]), vec![3..6]);
+        assert_eq!(marked([5, 4, 3]), vec![3..6]);
+    }
+
+    #[test]
+    fn marks_within_the_gap_merge() {
+        let gap = DirtyRanges::GAP;
+        assert_eq!(marked([0, gap]), vec![0..gap + 1]);
+        assert_eq!(marked([0, gap + 2]), vec![0..1, gap + 2..gap + 3]);
+        let mut dirty = DirtyRanges::default();
+        dirty.mark_range(10..20);
+        dirty.mark_range(5..8);
+        dirty.mark_range(0..0);
+        assert_eq!(dirty.take(), vec![5..20]);
+    }
+
+    #[test]
+    fn ranges_collapse_past_the_limit() {
+        let count = DirtyRanges::MAX_RANGES * 3;
+        let indices: Vec<usize> = (0..count).map(|i| i * 100).collect();
+        let ranges = marked(indices.iter().rev().copied());
+        assert!(ranges.len() <= DirtyRanges::MAX_RANGES);
+        assert!(ranges.windows(2).all(|w| w[0].end < w[1].start));
+        assert!(indices.iter().all(|i| ranges.iter().any(|r| r.contains(i))));
+    }
+
+    #[test]
+    fn take_clears_the_changes() {
+        let mut dirty = DirtyRanges::default();
+        dirty.mark(1);
+        assert_eq!(dirty.take(), vec![1..2]);
+        assert!(dirty.take().is_empty());
+    }
+}
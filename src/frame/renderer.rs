@@ -1,40 +1,165 @@
-use std::{mem::size_of, vec::Vec};
+use std::{
+    mem::{size_of, size_of_val},
+    ops::Range,
+};
 
-use bytemuck::{Pod, Zeroable};
+use bytemuck::Pod;
 
-use log::debug;
 use wgpu::{
     include_wgsl, BufferUsages, Device, MultisampleState, RenderPass, RenderPipeline,
-    RenderPipelineDescriptor, SurfaceConfiguration,
+    RenderPipelineDescriptor,
 };
 
-use crate::{
-    handle::{Handle, HandleLike},
-    manager::{BBox, Vertex},
-};
+use crate::{handle::Handle, manager::Vertex};
 
-use super::FrameData;
+use super::{
+    data::{GradientData, StopData},
+    store::Camera,
+    FrameData, FrameStore,
+};
 
 pub struct FrameRenderer {
-    data: Vec<FrameData>,
     pipeline: RenderPipeline,
     index_pipeline: RenderPipeline,
+    shadow_pipeline: RenderPipeline,
+    backdrop_pipeline: RenderPipeline,
+    backdrop_bg_layout: wgpu::BindGroupLayout,
+    backdrop_sampler: wgpu::Sampler,
+    /// copy of the target taken before a frame with a backdrop blur is drawn
+    backdrop: Option<(wgpu::Texture, wgpu::BindGroup)>,
+    /// frame indices in draw order
+    order: Vec<u32>,
+    /// first draw position and frame indices of every run of consecutive indices in `order`
+    runs: Vec<(u32, Range<u32>)>,
+    /// draw positions of frames with a shadow, drawn right before each of them
+    shadows: Vec<u32>,
+    /// draw positions of frames with a backdrop blur
+    backdrops: Vec<u32>,
+    group_pipeline: RenderPipeline,
+    /// draw positions of every opacity group
+    groups: Vec<Range<u32>>,
+    /// how deep opacity groups are nested
+    group_depth: usize,
+    /// a texture for every depth of nested opacity groups
+    group_targets: Vec<GroupTarget>,
+    /// format of the views drawn into, colors are blended in linear space so it is an srgb one
+    format: wgpu::TextureFormat,
+    /// draws `encoded` into targets that can't be viewed as srgb
+    encode_pipeline: Option<RenderPipeline>,
+    /// srgb texture drawn into instead of a target that can't be viewed as srgb
+    encoded: Option<GroupTarget>,
+    /// samples per pixel of every pipeline but the index pipeline
+    sample_count: u32,
+    /// multisampled texture resolved into the target of `render`
+    msaa: Option<wgpu::Texture>,
     frame_buffer_handle: wgpu::Buffer,
-    changed: Option<usize>,
+    camera_bg_layout: wgpu::BindGroupLayout,
     camera_bg_handle: wgpu::BindGroup,
     camera_buffer_handle: wgpu::Buffer,
-    camera_data: Vec<Camera>,
-}
-#[derive(Pod, Zeroable, Clone, Copy, Debug)]
-#[repr(C)]
-pub struct Camera {
-    bbox: BBox,
+    gradient_buffer_handle: wgpu::Buffer,
+    stop_buffer_handle: wgpu::Buffer,
+    count: u32,
+    overlay_buffer_handle: wgpu::Buffer,
+    overlay_count: u32,
 }
 
 pub type FrameHandle = Handle<FrameData>;
 
+/// a larger buffer with the usage of `buffer` when it is smaller than `bytes`,
+/// what it held is not copied
+pub(crate) fn grown(
+    device: &Device,
+    buffer: &wgpu::Buffer,
+    label: &str,
+    bytes: u64,
+) -> Option<wgpu::Buffer> {
+    if bytes <= buffer.size() {
+        return None;
+    }
+    let size = bytes.max(buffer.size() * 2);
+    Some(device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size,
+        usage: buffer.usage(),
+        mapped_at_creation: false,
+    }))
+}
+
+/// writes the `changed` ranges of `data` to the same place in `buffer`, or all of `data` when `all`
+fn upload<T: Pod>(
+    queue: &wgpu::Queue,
+    buffer: &wgpu::Buffer,
+    data: &[T],
+    changed: Vec<Range<usize>>,
+    all: bool,
+) {
+    for range in upload_ranges(changed, data.len(), all) {
+        let offset = (range.start * size_of::<T>()) as u64;
+        queue.write_buffer(buffer, offset, bytemuck::cast_slice(&data[range]));
+    }
+}
+
+/// a new buffer holds nothing yet, so `all` replaces the changes with one range over `len`
+fn upload_ranges(changed: Vec<Range<usize>>, len: usize, all: bool) -> Vec<Range<usize>> {
+    match all {
+        true => std::iter::once(0..len).collect(),
+        false => changed,
+    }
+}
+
+/// texture a `FrameRenderer::render_positions` pass draws into
+#[derive(Clone, Copy)]
+struct PassTarget<'a> {
+    texture: &'a wgpu::Texture,
+    view: &'a wgpu::TextureView,
+    /// drawn into instead of `view` and resolved into it at the end of every pass
+    msaa: Option<&'a wgpu::TextureView>,
+    /// backdrops need to copy the texture
+    copyable: bool,
+    /// how many opacity groups the texture is nested in
+    depth: usize,
+}
+
+/// what every pass of a `FrameRenderer::render` draws with
+#[derive(Clone, Copy)]
+struct DrawInputs<'a> {
+    vertex_buffer: &'a wgpu::Buffer,
+    content: &'a [&'a dyn FrameContent],
+}
+
+/// an opacity group is drawn into `texture` before it is drawn with its opacity
+struct GroupTarget {
+    texture: wgpu::Texture,
+    /// binds `texture` like the backdrop copy
+    bind_group: wgpu::BindGroup,
+    msaa: Option<wgpu::Texture>,
+}
+
+enum Split {
+    /// draw position of a frame with a backdrop blur
+    Backdrop(u32),
+    /// draw positions of an opacity group
+    Group(Range<u32>),
+}
+
+/// instances drawn on top of the frames they belong to, like text, see `FrameStore::content_breaks`
+pub trait FrameContent {
+    /// `(frame, instances)` to draw right before the frame at each index
+    fn draw_breaks(&self) -> &[(u32, Range<u32>)];
+    /// draws `instances` with its own pipeline and bind groups
+    fn draw<'a: 'rp, 'rp>(&'a self, render_pass: &mut RenderPass<'rp>, instances: Range<u32>);
+}
+
 impl FrameRenderer {
-    pub fn new(device: &Device, config: &SurfaceConfiguration) -> Self {
+    /// starting number of gradients and color stops, their buffers grow past it
+    const GRADIENT_CAPACITY: usize = 10000;
+
+    /// the index pipeline is always single sampled so picking returns exact indices,
+    /// targets are drawn through a view with `format`, when it isn't srgb everything is drawn
+    /// into an srgb texture first and encoded into the target at the end
+    pub fn new(device: &Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let target_format = format;
+        let format = format.add_srgb_suffix();
         let shader = include_wgsl!("shader.wgsl");
         let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("frame shader"),
@@ -46,101 +171,179 @@ impl FrameRenderer {
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
+        let gradient_buffer_handle = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gradient buffer"),
+            size: (size_of::<GradientData>() * Self::GRADIENT_CAPACITY) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let stop_buffer_handle = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gradient stop buffer"),
+            size: (size_of::<StopData>() * Self::GRADIENT_CAPACITY) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let storage_entry = |binding, visibility| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
         let camera_bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Camera buffer layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                storage_entry(0, wgpu::ShaderStages::VERTEX),
+                storage_entry(1, wgpu::ShaderStages::FRAGMENT),
+                storage_entry(2, wgpu::ShaderStages::FRAGMENT),
+            ],
+        });
+        let camera_bg_handle = Self::camera_bg(
+            device,
+            &camera_bg_layout,
+            &camera_buffer_handle,
+            &gradient_buffer_handle,
+            &stop_buffer_handle,
+        );
+        let backdrop_bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("backdrop layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
                 },
-                count: None,
-            }],
+            ],
         });
-        let camera_bg_handle = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("camera bg"),
-            layout: &camera_bg_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer_handle.as_entire_binding(),
-            }],
+        let backdrop_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("backdrop sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
         });
-        let pipeline_layout_descriptor = wgpu::PipelineLayoutDescriptor {
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("frame pipeline layout"),
             bind_group_layouts: &[&camera_bg_layout],
             push_constant_ranges: &[],
-        };
-        let pipeline_layout = device.create_pipeline_layout(&pipeline_layout_descriptor);
+        });
+        let backdrop_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("backdrop pipeline layout"),
+                bind_group_layouts: &[&camera_bg_layout, &backdrop_bg_layout],
+                push_constant_ranges: &[],
+            });
 
         let color_targets = [Some(wgpu::ColorTargetState {
-            format: config.format,
+            format,
             blend: Some(wgpu::BlendState::ALPHA_BLENDING),
             write_mask: wgpu::ColorWrites::ALL,
         })];
-
-        let pipeline_descriptor = RenderPipelineDescriptor {
-            label: Some("frame pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &module,
-                entry_point: "vs_main",
-                buffers: &[Vertex::desc(), FrameData::desc()],
-            },
-            primitive: Vertex::state(),
-            depth_stencil: None,
-            multisample: MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &module,
-                entry_point: "fs_main",
-                targets: &color_targets,
-            }),
-            multiview: None,
-        };
-        let pipeline = device.create_render_pipeline(&pipeline_descriptor);
-
-        let index_pipeline_layout_descriptor = wgpu::PipelineLayoutDescriptor {
-            label: Some("index pipeline layout"),
-            bind_group_layouts: &[&camera_bg_layout],
-            push_constant_ranges: &[],
-        };
-        let index_pipeline_layout =
-            device.create_pipeline_layout(&index_pipeline_layout_descriptor);
-
+        // opacity groups are drawn into transparent textures, which leaves their colors premultiplied
+        let group_color_targets = [Some(wgpu::ColorTargetState {
+            format,
+            blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+        let encode_color_targets = [Some(wgpu::ColorTargetState {
+            format: target_format,
+            blend: None,
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
         let index_color_targets = [Some(wgpu::ColorTargetState {
             format: wgpu::TextureFormat::R32Uint,
             blend: None,
             write_mask: wgpu::ColorWrites::ALL,
         })];
 
-        let index_pipeline_descriptor = RenderPipelineDescriptor {
-            label: Some("frame index pipeline"),
-            layout: Some(&index_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &module,
-                entry_point: "vs_index_main",
-                buffers: &[Vertex::desc(), FrameData::desc()],
-            },
-            primitive: Vertex::state(),
-            depth_stencil: None,
-            multisample: MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &module,
-                entry_point: "fs_index_main",
-                targets: &index_color_targets,
-            }),
-            multiview: None,
+        // every pipeline draws one quad per `FrameData` instance
+        let create_pipeline = |label, layout, vs, fs, targets, count| {
+            device.create_render_pipeline(&RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: vs,
+                    buffers: &[Vertex::desc(), FrameData::desc()],
+                },
+                primitive: Vertex::state(),
+                depth_stencil: None,
+                multisample: MultisampleState {
+                    count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: fs,
+                    targets,
+                }),
+                multiview: None,
+            })
         };
-        let index_pipeline = device.create_render_pipeline(&index_pipeline_descriptor);
+        let pipeline = create_pipeline(
+            "frame pipeline",
+            &pipeline_layout,
+            "vs_main",
+            "fs_main",
+            &color_targets,
+            sample_count,
+        );
+        let index_pipeline = create_pipeline(
+            "frame index pipeline",
+            &pipeline_layout,
+            "vs_index_main",
+            "fs_index_main",
+            &index_color_targets,
+            1,
+        );
+        let shadow_pipeline = create_pipeline(
+            "frame shadow pipeline",
+            &pipeline_layout,
+            "vs_shadow_main",
+            "fs_shadow_main",
+            &color_targets,
+            sample_count,
+        );
+        let backdrop_pipeline = create_pipeline(
+            "frame backdrop pipeline",
+            &backdrop_pipeline_layout,
+            "vs_backdrop_main",
+            "fs_backdrop_main",
+            &color_targets,
+            sample_count,
+        );
+        let group_pipeline = create_pipeline(
+            "frame group pipeline",
+            &backdrop_pipeline_layout,
+            "vs_group_main",
+            "fs_group_main",
+            &group_color_targets,
+            sample_count,
+        );
+        let encode_pipeline = (!target_format.is_srgb()).then(|| {
+            create_pipeline(
+                "frame encode pipeline",
+                &backdrop_pipeline_layout,
+                "vs_encode_main",
+                "fs_encode_main",
+                &encode_color_targets,
+                1,
+            )
+        });
 
         let buffer_handle = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("frame instance buffer"),
@@ -148,74 +351,601 @@ impl FrameRenderer {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
+        let overlay_buffer_handle = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("overlay instance buffer"),
+            size: FrameData::BUFFER_INIT_BYTE_COUNT,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
         Self {
             pipeline,
             frame_buffer_handle: buffer_handle,
             index_pipeline,
-            data: vec![],
-            changed: None,
+            shadow_pipeline,
+            backdrop_pipeline,
+            backdrop_bg_layout,
+            backdrop_sampler,
+            backdrop: None,
+            order: vec![],
+            runs: vec![],
+            shadows: vec![],
+            backdrops: vec![],
+            group_pipeline,
+            groups: vec![],
+            group_depth: 0,
+            group_targets: vec![],
+            format,
+            encode_pipeline,
+            encoded: None,
+            sample_count,
+            msaa: None,
             camera_buffer_handle,
-            camera_data: vec![],
+            camera_bg_layout,
             camera_bg_handle,
+            gradient_buffer_handle,
+            stop_buffer_handle,
+            count: 0,
+            overlay_buffer_handle,
+            overlay_count: 0,
         }
     }
-    pub fn prepare(&mut self, queue: &wgpu::Queue) {
-        queue.write_buffer(
-            &self.frame_buffer_handle,
-            0,
-            bytemuck::cast_slice(&self.data[..]),
-        );
-        queue.write_buffer(
+    fn camera_bg(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        cameras: &wgpu::Buffer,
+        gradients: &wgpu::Buffer,
+        stops: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("camera bg"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: cameras.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: gradients.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: stops.as_entire_binding(),
+                },
+            ],
+        })
+    }
+    /// reallocates the instance and camera buffers when the frames outgrow them,
+    /// returns which of them have to be uploaded in full
+    fn grow(&mut self, device: &Device, frames: &FrameStore) -> [bool; 2] {
+        let mut res = [false; 2];
+        let bytes = size_of_val(frames.data()) as u64;
+        let label = "frame instance buffer";
+        if let Some(buffer) = grown(device, &self.frame_buffer_handle, label, bytes) {
+            self.frame_buffer_handle = buffer;
+            res[0] = true;
+        }
+        let bytes = size_of_val(frames.cameras()) as u64;
+        if let Some(buffer) = grown(device, &self.camera_buffer_handle, "camera buffer", bytes) {
+            res[1] = true;
+            self.camera_buffer_handle = buffer;
+            self.rebind(device);
+        }
+        res
+    }
+    /// reallocates the gradient and stop buffers when they are too small for `gradients`
+    /// and `stops`, both are always uploaded in full
+    fn grow_gradients(&mut self, device: &Device, gradients: &[GradientData], stops: &[StopData]) {
+        let bytes = size_of_val(gradients) as u64;
+        let gradient = grown(device, &self.gradient_buffer_handle, "gradient buffer", bytes);
+        let bytes = size_of_val(stops) as u64;
+        let stop = grown(device, &self.stop_buffer_handle, "gradient stop buffer", bytes);
+        if gradient.is_none() && stop.is_none() {
+            return;
+        }
+        if let Some(buffer) = gradient {
+            self.gradient_buffer_handle = buffer;
+        }
+        if let Some(buffer) = stop {
+            self.stop_buffer_handle = buffer;
+        }
+        self.rebind(device);
+    }
+    /// the camera bind group after one of its buffers was replaced
+    fn rebind(&mut self, device: &Device) {
+        self.camera_bg_handle = Self::camera_bg(
+            device,
+            &self.camera_bg_layout,
             &self.camera_buffer_handle,
-            0,
-            bytemuck::cast_slice(&self.camera_data[..]),
+            &self.gradient_buffer_handle,
+            &self.stop_buffer_handle,
         );
-
-        self.changed = None;
+    }
+    pub fn prepare(&mut self, device: &Device, frames: &mut FrameStore, queue: &wgpu::Queue) {
+        frames.solve_cameras();
+        frames.solve_order();
+        let [all_frames, all_cameras] = self.grow(device, frames);
+        if let Some((gradients, stops)) = frames.take_gradients() {
+            self.grow_gradients(device, &gradients, &stops);
+            queue.write_buffer(&self.gradient_buffer_handle, 0, bytemuck::cast_slice(&gradients));
+            queue.write_buffer(&self.stop_buffer_handle, 0, bytemuck::cast_slice(&stops));
+        }
+        let changed = frames.take_changed();
+        upload(queue, &self.frame_buffer_handle, frames.data(), changed, all_frames);
+        let changed = frames.take_changed_cameras();
+        upload(queue, &self.camera_buffer_handle, frames.cameras(), changed, all_cameras);
+        self.count = frames.len() as u32;
+        self.order = frames.draw_order().to_vec();
+        self.runs = Self::runs(&self.order);
+        self.shadows = self.positions(frames.data(), |f| f.shadow_color.a > 0);
+        self.backdrops = self.positions(frames.data(), |f| f.backdrop_blur > 0.into());
+        self.groups = frames.opacity_groups().to_vec();
+        let mut ends: Vec<u32> = vec![];
+        self.group_depth = 0;
+        for group in &self.groups {
+            while ends.last().is_some_and(|&end| end <= group.start) {
+                ends.pop();
+            }
+            ends.push(group.end);
+            self.group_depth = self.group_depth.max(ends.len());
+        }
+    }
+    /// quads drawn with the window camera on top of every frame, they are not hit tested
+    pub fn prepare_overlay(&mut self, device: &Device, quads: &[FrameData], queue: &wgpu::Queue) {
+        let bytes = size_of_val(quads) as u64;
+        let label = "overlay instance buffer";
+        if let Some(buffer) = grown(device, &self.overlay_buffer_handle, label, bytes) {
+            self.overlay_buffer_handle = buffer;
+        }
+        queue.write_buffer(&self.overlay_buffer_handle, 0, bytemuck::cast_slice(quads));
+        self.overlay_count = quads.len() as u32;
     }
     fn render_pipeline<'a: 'rp, 'rp>(&'a self, render_pass: &mut RenderPass<'rp>) {
         render_pass.set_vertex_buffer(1, self.frame_buffer_handle.slice(..));
         render_pass.set_bind_group(0, &self.camera_bg_handle, &[]);
-        render_pass.draw(0..4 as u32, 0..self.data.len() as u32);
+        self.draw_positions(render_pass, 0..self.count);
     }
-    pub fn render<'a: 'rp, 'rp>(&'a self, render_pass: &mut RenderPass<'rp>) {
-        //debug!("frames: {:?}", self.data);
-        render_pass.set_pipeline(&self.pipeline);
-        self.render_pipeline(render_pass);
+    fn positions(&self, frames: &[FrameData], f: impl Fn(&FrameData) -> bool) -> Vec<u32> {
+        (0..self.order.len() as u32)
+            .filter(|&p| f(&frames[self.order[p as usize] as usize]))
+            .collect()
     }
+    fn runs(order: &[u32]) -> Vec<(u32, Range<u32>)> {
+        let mut res: Vec<(u32, Range<u32>)> = vec![];
+        for (position, &index) in order.iter().enumerate() {
+            match res.last_mut() {
+                Some((_, run)) if run.end == index => run.end += 1,
+                _ => res.push((position as u32, index..index + 1)),
+            }
+        }
+        res
+    }
+    /// draws the frames at `positions` in draw order, one instanced draw per run
+    fn draw_positions(&self, render_pass: &mut RenderPass, positions: Range<u32>) {
+        let first = self
+            .runs
+            .partition_point(|(start, run)| start + run.len() as u32 <= positions.start);
+        for (start, run) in &self.runs[first..] {
+            if *start >= positions.end {
+                break;
+            }
+            let from = positions.start.max(*start) - start;
+            let to = positions.end.min(start + run.len() as u32) - start;
+            render_pass.draw(0..4, run.start + from..run.start + to);
+        }
+    }
+    /// draws every frame, their shadows, `content` in order and the overlay into `target`,
+    /// the pass is split before every frame with a backdrop blur to copy what is behind it,
+    /// backdrops are skipped when `target` can't be copied from,
+    /// `target` needs to allow a view with the format the renderer was created with
+    pub fn render(
+        &mut self,
+        device: &Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        vertex_buffer: &wgpu::Buffer,
+        content: &[&dyn FrameContent],
+        clear: wgpu::Color,
+    ) {
+        let output = target;
+        let mut encoded = self.encoded.take();
+        if self.encode_pipeline.is_some() {
+            let fits = |e: &GroupTarget| e.texture.size() == output.size();
+            if !encoded.as_ref().is_some_and(fits) {
+                encoded = Some(self.group_target(device, output));
+            }
+        }
+        let target = encoded.as_ref().map_or(output, |e| &e.texture);
+        let copyable = target.usage().contains(wgpu::TextureUsages::COPY_SRC);
+        if !self.backdrops.is_empty() {
+            self.resize_backdrop(device, target);
+        }
+        self.resize_groups(device, target);
+        let old = self.msaa.take();
+        self.msaa = self.msaa_texture(device, target, old);
+        let view = target.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(self.format),
+            ..Default::default()
+        });
+        let msaa = self.msaa.as_ref().map(|t| t.create_view(&Default::default()));
+        let target = PassTarget {
+            texture: target,
+            view: &view,
+            msaa: msaa.as_ref(),
+            copyable,
+            depth: 0,
+        };
+        self.render_positions(
+            encoder,
+            target,
+            DrawInputs {
+                vertex_buffer,
+                content,
+            },
+            0..self.count,
+            wgpu::LoadOp::Clear(clear),
+        );
+        if self.overlay_count > 0 {
+            let mut render_pass =
+                Self::begin_pass(encoder, target, wgpu::LoadOp::Load, vertex_buffer);
+            render_pass.set_bind_group(0, &self.camera_bg_handle, &[]);
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_vertex_buffer(1, self.overlay_buffer_handle.slice(..));
+            render_pass.draw(0..4, 0..self.overlay_count);
+        }
+        if let (Some(pipeline), Some(encoded)) = (&self.encode_pipeline, &encoded) {
+            let view = output.create_view(&wgpu::TextureViewDescriptor::default());
+            let target = PassTarget {
+                texture: output,
+                view: &view,
+                msaa: None,
+                copyable: false,
+                depth: 0,
+            };
+            let mut render_pass =
+                Self::begin_pass(encoder, target, wgpu::LoadOp::Load, vertex_buffer);
+            render_pass.set_vertex_buffer(1, self.frame_buffer_handle.slice(..));
+            render_pass.set_bind_group(0, &self.camera_bg_handle, &[]);
+            render_pass.set_bind_group(1, &encoded.bind_group, &[]);
+            render_pass.set_pipeline(pipeline);
+            render_pass.draw(0..4, 0..1);
+        }
+        self.encoded = encoded;
+    }
+    /// draws the frames at the draw positions in `positions`,
+    /// opacity groups inside of them are drawn into the group texture of `target.depth` first
+    fn render_positions(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: PassTarget,
+        inputs: DrawInputs,
+        positions: Range<u32>,
+        mut load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        // nested groups are drawn by the group around them, a group's own frame starts its positions
+        let mut splits = vec![];
+        let mut nested_until = positions.start;
+        for group in &self.groups {
+            let own = target.depth > 0 && group.start == positions.start;
+            if group.start >= nested_until && !own && positions.contains(&group.start) {
+                splits.push(Split::Group(group.clone()));
+                nested_until = group.end;
+            }
+        }
+        let grouped = |p: &u32| splits.iter().any(|s| matches!(s, Split::Group(g) if g.contains(p)));
+        let backdrops = self
+            .backdrops
+            .iter()
+            .filter(|&b| target.copyable && positions.contains(b) && !grouped(b))
+            .map(|&b| Split::Backdrop(b))
+            .collect::<Vec<_>>();
+        splits.extend(backdrops);
+        splits.sort_by_key(|s| match s {
+            Split::Group(g) => g.start,
+            Split::Backdrop(b) => *b,
+        });
 
-    pub fn render_index<'rp>(&'rp self, render_pass: &mut RenderPass<'rp>) {
-        //debug!("frames: {:?}", self.data);
-        render_pass.set_pipeline(&self.index_pipeline);
-        self.render_pipeline(render_pass);
+        let mut start = positions.start;
+        let mut shadows_from = positions.start;
+        for split in splits {
+            match split {
+                Split::Backdrop(backdrop) => {
+                    // the shadow of a backdrop frame is part of what it blurs
+                    self.draw_pass(
+                        encoder,
+                        target,
+                        load,
+                        inputs,
+                        start..backdrop,
+                        shadows_from..backdrop + 1,
+                    );
+                    load = wgpu::LoadOp::Load;
+                    let Some((texture, bind_group)) = &self.backdrop else {
+                        continue;
+                    };
+                    encoder.copy_texture_to_texture(
+                        target.texture.as_image_copy(),
+                        texture.as_image_copy(),
+                        target.texture.size(),
+                    );
+                    let mut render_pass =
+                        Self::begin_pass(encoder, target, load, inputs.vertex_buffer);
+                    render_pass.set_vertex_buffer(1, self.frame_buffer_handle.slice(..));
+                    render_pass.set_bind_group(0, &self.camera_bg_handle, &[]);
+                    render_pass.set_bind_group(1, bind_group, &[]);
+                    render_pass.set_pipeline(&self.backdrop_pipeline);
+                    let index = self.order[backdrop as usize];
+                    render_pass.draw(0..4, index..index + 1);
+                    start = backdrop;
+                    shadows_from = backdrop + 1;
+                }
+                Split::Group(group) => {
+                    self.draw_pass(
+                        encoder,
+                        target,
+                        load,
+                        inputs,
+                        start..group.start,
+                        shadows_from..group.start,
+                    );
+                    load = wgpu::LoadOp::Load;
+                    let group_target = &self.group_targets[target.depth];
+                    let texture = &group_target.texture;
+                    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                    let msaa = group_target.msaa.as_ref();
+                    let msaa = msaa.map(|t| t.create_view(&Default::default()));
+                    self.render_positions(
+                        encoder,
+                        PassTarget {
+                            texture,
+                            view: &view,
+                            msaa: msaa.as_ref(),
+                            copyable: true,
+                            depth: target.depth + 1,
+                        },
+                        inputs,
+                        group.clone(),
+                        wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    );
+                    let mut render_pass =
+                        Self::begin_pass(encoder, target, load, inputs.vertex_buffer);
+                    render_pass.set_vertex_buffer(1, self.frame_buffer_handle.slice(..));
+                    render_pass.set_bind_group(0, &self.camera_bg_handle, &[]);
+                    render_pass.set_bind_group(1, &group_target.bind_group, &[]);
+                    render_pass.set_pipeline(&self.group_pipeline);
+                    let index = self.order[group.start as usize];
+                    render_pass.draw(0..4, index..index + 1);
+                    start = group.end;
+                    shadows_from = group.end;
+                }
+            }
+        }
+        self.draw_pass(
+            encoder,
+            target,
+            load,
+            inputs,
+            start..positions.end,
+            shadows_from..positions.end,
+        );
     }
-    pub fn add(&mut self, frame: FrameData) -> usize {
-        self.camera_data.push(Camera { bbox: frame.data });
-        self.data.push(frame);
-        self.changed = Some(self.data.len() - 1);
-        return self.data.len() - 1;
+    /// one pass of `draw_frames`
+    fn draw_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: PassTarget,
+        load: wgpu::LoadOp<wgpu::Color>,
+        inputs: DrawInputs,
+        frames: Range<u32>,
+        shadows: Range<u32>,
+    ) {
+        let mut render_pass = Self::begin_pass(encoder, target, load, inputs.vertex_buffer);
+        render_pass.set_vertex_buffer(1, self.frame_buffer_handle.slice(..));
+        render_pass.set_bind_group(0, &self.camera_bg_handle, &[]);
+        self.draw_frames(&mut render_pass, frames, shadows, inputs.content);
+    }
+    fn begin_pass<'e>(
+        encoder: &'e mut wgpu::CommandEncoder,
+        target: PassTarget<'e>,
+        load: wgpu::LoadOp<wgpu::Color>,
+        vertex_buffer: &'e wgpu::Buffer,
+    ) -> RenderPass<'e> {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("frame render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target.msaa.unwrap_or(target.view),
+                resolve_target: target.msaa.map(|_| target.view),
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass
+    }
+    /// draws the frames at the draw positions in `frames`, the shadows at positions in `shadows`
+    /// right before their frames, and the content that a frame covers right before it and its shadow
+    fn draw_frames<'a: 'rp, 'rp>(
+        &'a self,
+        render_pass: &mut RenderPass<'rp>,
+        frames: Range<u32>,
+        shadows: Range<u32>,
+        content: &[&'rp dyn FrameContent],
+    ) {
+        // shadows sort after the content at the same index
+        let mut breaks: Vec<(u32, usize, Range<u32>)> = content
+            .iter()
+            .enumerate()
+            .flat_map(|(c, content)| {
+                content
+                    .draw_breaks()
+                    .iter()
+                    .filter(|(i, _)| frames.start < *i && *i <= frames.end)
+                    .map(move |(i, instances)| (*i, c, instances.clone()))
+            })
+            .chain(
+                self.shadows
+                    .iter()
+                    .filter(|s| shadows.contains(s))
+                    .map(|&s| (s, content.len(), s..s + 1)),
+            )
+            .collect();
+        breaks.sort_by_key(|(i, c, _)| (*i, *c));
+        let mut start = frames.start;
+        for (index, c, instances) in breaks {
+            if start < index {
+                render_pass.set_pipeline(&self.pipeline);
+                self.draw_positions(render_pass, start..index);
+            }
+            start = index;
+            match content.get(c) {
+                Some(content) => {
+                    content.draw(render_pass, instances);
+                    render_pass.set_vertex_buffer(1, self.frame_buffer_handle.slice(..));
+                    render_pass.set_bind_group(0, &self.camera_bg_handle, &[]);
+                }
+                None => {
+                    render_pass.set_pipeline(&self.shadow_pipeline);
+                    let frame = self.order[instances.start as usize];
+                    render_pass.draw(0..4, frame..frame + 1);
+                }
+            }
+        }
+        if start < frames.end {
+            render_pass.set_pipeline(&self.pipeline);
+            self.draw_positions(render_pass, start..frames.end);
+        }
+    }
+    /// keeps a group texture for every depth of nested opacity groups at the size of `target`
+    fn resize_groups(&mut self, device: &Device, target: &wgpu::Texture) {
+        let matches = |t: &wgpu::Texture| t.size() == target.size();
+        if !self.group_targets.iter().all(|g| matches(&g.texture)) {
+            self.group_targets.clear();
+        }
+        while self.group_targets.len() < self.group_depth {
+            self.group_targets.push(self.group_target(device, target));
+        }
+    }
+    /// an srgb texture the size of `target` that can be drawn into and then sampled
+    fn group_target(&self, device: &Device, target: &wgpu::Texture) -> GroupTarget {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("frame group texture"),
+            size: target.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        GroupTarget {
+            bind_group: self.texture_bind_group(device, &texture),
+            msaa: self.msaa_texture(device, &texture, None),
+            texture,
+        }
     }
-    pub fn update(&mut self, index: usize, bounds: &BBox) {
-        let frame: &mut FrameData = &mut self.data[index];
-        frame.data = *bounds;
-        self.camera_data[index].bbox = *bounds;
-        self.changed = match self.changed {
-            None => Some(index),
-            Some(u) => Some(usize::max(u, index)),
+    /// the multisampled texture drawn into instead of `target`, reuses `old` when it still fits,
+    /// `None` without msaa
+    fn msaa_texture(
+        &self,
+        device: &Device,
+        target: &wgpu::Texture,
+        old: Option<wgpu::Texture>,
+    ) -> Option<wgpu::Texture> {
+        if self.sample_count <= 1 {
+            return None;
+        }
+        if let Some(texture) = old {
+            if texture.size() == target.size() {
+                return Some(texture);
+            }
         }
+        Some(device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("msaa texture"),
+            size: target.size(),
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        }))
     }
-    pub fn resize(&mut self, bounds: &BBox) {
-        
+    /// binds `texture` like the backdrop copy
+    fn texture_bind_group(&self, device: &Device, texture: &wgpu::Texture) -> wgpu::BindGroup {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("frame texture bg"),
+            layout: &self.backdrop_bg_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.backdrop_sampler),
+                },
+            ],
+        })
     }
-    pub fn update_color(&mut self, index: usize, color: [u8; 4]) {
-        let frame = &mut self.data[index];
-        frame.color = color;
-        self.changed = match self.changed {
-            None => Some(index),
-            Some(u) => Some(usize::max(u, index)),
+    /// keeps the backdrop copy at the size of `target`
+    fn resize_backdrop(&mut self, device: &Device, target: &wgpu::Texture) {
+        if let Some((texture, _)) = &self.backdrop {
+            if texture.size() == target.size() {
+                return;
+            }
         }
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("backdrop texture"),
+            size: target.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("backdrop bg"),
+            layout: &self.backdrop_bg_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.backdrop_sampler),
+                },
+            ],
+        });
+        self.backdrop = Some((texture, bind_group));
     }
-    pub fn get(&self, index: usize) -> &FrameData {
-        &self.data[index]
+
+    pub fn render_index<'rp>(&'rp self, render_pass: &mut RenderPass<'rp>) {
+        //debug!("frames: {:?}", self.data);
+        render_pass.set_pipeline(&self.index_pipeline);
+        self.render_pipeline(render_pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grown_buffers_upload_everything() {
+        let changed = vec![2..3, 7..9];
+        assert_eq!(upload_ranges(changed.clone(), 10, false), changed);
+        assert_eq!(upload_ranges(changed, 10, true), vec![0..10]);
+        assert_eq!(upload_ranges(vec![], 4, true), vec![0..4]);
     }
 }
@@ -7,6 +7,22 @@ struct VertexInput {
     @location(2) margin: vec4<i32>,
     @location(3) color: vec4<f32>,
     @location(4) camera_index: u32,
+    // top left, top right, bottom right, bottom left
+    @location(5) radius: vec4<i32>,
+    // [top, bottom, left, right] like margin
+    @location(6) border: vec4<i32>,
+    @location(7) border_top: vec4<f32>,
+    @location(8) border_bottom: vec4<f32>,
+    @location(9) border_left: vec4<f32>,
+    @location(10) border_right: vec4<f32>,
+    // index into gradient_array plus one, 0 uses color
+    @location(11) gradient: u32,
+    // x offset, y offset, blur, spread
+    @location(12) shadow: vec4<i32>,
+    @location(13) shadow_color: vec4<f32>,
+    @location(14) backdrop_blur: i32,
+    // multiplies the alpha of the frame and of its opacity group
+    @location(15) opacity: vec2<f32>,
 };
 
 const VUNIT_PRECISION = 64; // 1 << 6
@@ -14,15 +30,70 @@ const VUNIT_PRECISION = 64; // 1 << 6
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) screen: vec2<f32>,
+    @location(2) @interpolate(flat) clip: vec4<f32>,
+    @location(3) local: vec2<f32>,
+    @location(4) @interpolate(flat) size: vec2<f32>,
+    @location(5) @interpolate(flat) radius: vec4<f32>,
+    @location(6) @interpolate(flat) border: vec4<f32>,
+    @location(7) @interpolate(flat) border_top: vec4<f32>,
+    @location(8) @interpolate(flat) border_bottom: vec4<f32>,
+    @location(9) @interpolate(flat) border_left: vec4<f32>,
+    @location(10) @interpolate(flat) border_right: vec4<f32>,
+    @location(11) @interpolate(flat) gradient: u32,
+    @location(12) @interpolate(flat) opacity: f32,
 };
 
 struct CameraArray {
-    xywh: vec4<i32> 
+    xywh: vec4<i32>,
+    // scroll offset applied to everything drawn with this camera
+    offset: vec2<i32>,
+    // screen space xywh everything drawn with this camera is clipped to
+    clip: vec4<i32>,
+};
+
+struct Placement {
+    clip_position: vec4<f32>,
+    screen: vec2<f32>,
+    clip: vec4<f32>,
+    // position inside of the visible bounds
+    local: vec2<f32>,
+    size: vec2<f32>,
 };
 
 @group(0) @binding(0) 
 var<storage, read> camera_array: array<CameraArray>;
 
+const GRADIENT_LINEAR = 1u;
+const GRADIENT_RADIAL = 2u;
+
+struct Gradient {
+    kind: u32,
+    first: u32,
+    count: u32,
+    // linear: angle in radians, radial: center relative to the visible bounds
+    params: vec4<f32>,
+};
+
+struct GradientStop {
+    // already in linear space
+    color: vec4<f32>,
+    offset: f32,
+};
+
+@group(0) @binding(1)
+var<storage, read> gradient_array: array<Gradient>;
+
+@group(0) @binding(2)
+var<storage, read> stop_array: array<GradientStop>;
+
+
+// colors arrive as srgb and are blended in linear space, the srgb target encodes them again
+fn srgb_to_linear(c: vec4<f32>) -> vec4<f32> {
+    let curve = pow((c.rgb + 0.055) / 1.055, vec3<f32>(2.4));
+    let rgb = select(curve, c.rgb / 12.92, c.rgb <= vec3<f32>(0.04045));
+    return vec4<f32>(rgb, c.a);
+}
 
 /// expects box to be xywh and margin [top, bottom, right, left]
 fn calculate_margin(box: vec4<i32>, margin: vec4<i32>) -> vec4<i32> {
@@ -31,20 +102,133 @@ fn calculate_margin(box: vec4<i32>, margin: vec4<i32>) -> vec4<i32> {
     return res;
 }
 
+fn place(v: VertexInput) -> Placement {
+    return place_rect(v, calculate_margin(v.vertex_xywh, v.margin));
+}
+
+/// places the quad at `xywh` instead of the frame's visible bounds
+fn place_rect(v: VertexInput, xywh: vec4<i32>) -> Placement {
+    var out: Placement;
+    let cam = camera_array[v.camera_index];
+    let abs_pos = vec2<f32>(xywh.xy - cam.offset);
+    let abs_dim = vec2<f32>(xywh.zw);
+    let wv = vec4<f32>(camera_array[0].xywh);
+    let corner = v.position / vec2<f32>(2.0, -2.0) + vec2<f32>(0.5, 0.5);
+    let rel_pos: vec2<f32> = (abs_pos + wv.xy) / (wv.zw);
+    let rel_dim: vec2<f32> = abs_dim / (wv.zw);
+    out.clip_position = vec4<f32>( ((corner * rel_dim  + rel_pos) * vec2<f32>(2.0,-2.0) - vec2<f32>(1.0, -1.0)), 1.0,1.0);
+    out.screen = abs_pos + corner * abs_dim;
+    out.clip = vec4<f32>(cam.clip);
+    out.local = corner * abs_dim;
+    out.size = abs_dim;
+    return out;
+}
+
+fn clipped(screen: vec2<f32>, clip: vec4<f32>) -> bool {
+    return any(screen < clip.xy) || any(screen >= clip.xy + clip.zw);
+}
+
+/// signed distance to a box at the origin with `radius` [top left, top right, bottom right, bottom left]
+fn rounded_box(p: vec2<f32>, size: vec2<f32>, radius: vec4<f32>) -> f32 {
+    let half = size * 0.5;
+    let c = p - half;
+    let r = min(
+        select(select(radius.x, radius.y, c.x > 0.0), select(radius.w, radius.z, c.x > 0.0), c.y > 0.0),
+        min(half.x, half.y),
+    );
+    let q = abs(c) - half + r;
+    return min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0))) - r;
+}
+
+/// distance to the inside edge of the border, the corners shrink by the wider adjacent border
+fn inner_distance(local: vec2<f32>, size: vec2<f32>, radius: vec4<f32>, border: vec4<f32>) -> f32 {
+    let offset = vec2<f32>(border.z, border.x);
+    let inner_size = max(size - vec2<f32>(border.z + border.w, border.x + border.y), vec2<f32>(0.0));
+    let inner_radius = max(radius - vec4<f32>(
+        max(border.z, border.x),
+        max(border.w, border.x),
+        max(border.w, border.y),
+        max(border.z, border.y),
+    ), vec4<f32>(0.0));
+    return rounded_box(local - offset, inner_size, inner_radius);
+}
+
+/// position along the gradient, 0 at its start and 1 at its end
+fn gradient_offset(g: Gradient, local: vec2<f32>, size: vec2<f32>) -> f32 {
+    let c = local - size * 0.5;
+    if g.kind == GRADIENT_RADIAL {
+        let center = (g.params.xy - vec2<f32>(0.5)) * size;
+        return length((c - center) / max(size * 0.5, vec2<f32>(1e-6)));
+    }
+    // the gradient line spans the box along the angle, like css
+    let dir = vec2<f32>(sin(g.params.x), -cos(g.params.x));
+    let len = abs(size.x * dir.x) + abs(size.y * dir.y);
+    return dot(c, dir) / max(len, 1e-6) + 0.5;
+}
+
+fn gradient_color(index: u32, local: vec2<f32>, size: vec2<f32>, fallback: vec4<f32>) -> vec4<f32> {
+    if index == 0u || index > arrayLength(&gradient_array) {
+        return fallback;
+    }
+    let g = gradient_array[index - 1u];
+    let last = min(g.first + g.count, arrayLength(&stop_array));
+    if g.first >= last {
+        return fallback;
+    }
+    let t = gradient_offset(g, local, size);
+    var color = stop_array[g.first].color;
+    var prev = stop_array[g.first];
+    for (var i = g.first + 1u; i < last; i++) {
+        let stop = stop_array[i];
+        if t >= stop.offset {
+            color = stop.color;
+        } else if t > prev.offset {
+            color = mix(prev.color, stop.color, (t - prev.offset) / (stop.offset - prev.offset));
+        }
+        if t < stop.offset {
+            break;
+        }
+        prev = stop;
+    }
+    return color;
+}
+
+/// the side whose border `local` is relatively closest to
+fn border_color(in: VertexOutput) -> vec4<f32> {
+    let d = vec4<f32>(in.local.y, in.size.y - in.local.y, in.local.x, in.size.x - in.local.x)
+        / max(in.border, vec4<f32>(1e-6));
+    let d_masked = select(vec4<f32>(1e30), d, in.border > vec4<f32>(0.0));
+    let m = min(min(d_masked.x, d_masked.y), min(d_masked.z, d_masked.w));
+    if m == d_masked.x {
+        return in.border_top;
+    } else if m == d_masked.y {
+        return in.border_bottom;
+    } else if m == d_masked.z {
+        return in.border_left;
+    }
+    return in.border_right;
+}
+
 @vertex
 fn vs_main(
     v: VertexInput
 ) -> VertexOutput {
     var out: VertexOutput;
-    let xywh = calculate_margin(v.vertex_xywh, v.margin);
-    let abs_pos = vec2<f32>(xywh.xy);
-    let abs_dim = vec2<f32>(xywh.zw);
-    let wv = vec4<f32>(camera_array[0].xywh);
-    let cam = vec4<f32>(camera_array[v.camera_index].xywh);
-    let rel_pos: vec2<f32> = (abs_pos + wv.xy) / (wv.zw);
-    let rel_dim: vec2<f32> = abs_dim / (wv.zw);
-    out.clip_position = vec4<f32>( (((v.position / vec2<f32>(2.0, -2.0) + vec2<f32>(0.5, 0.5))  * rel_dim  + rel_pos) * vec2<f32>(2.0,-2.0) - vec2<f32>(1.0, -1.0)), 1.0,1.0);
-    out.color = v.color;
+    let p = place(v);
+    out.clip_position = p.clip_position;
+    out.screen = p.screen;
+    out.clip = p.clip;
+    out.color = srgb_to_linear(v.color);
+    out.local = p.local;
+    out.size = p.size;
+    out.radius = vec4<f32>(v.radius);
+    out.border = vec4<f32>(v.border);
+    out.border_top = srgb_to_linear(v.border_top);
+    out.border_bottom = srgb_to_linear(v.border_bottom);
+    out.border_left = srgb_to_linear(v.border_left);
+    out.border_right = srgb_to_linear(v.border_right);
+    out.gradient = v.gradient;
+    out.opacity = v.opacity.x;
     return out;
 }
 
@@ -52,12 +236,36 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    let outer = rounded_box(in.local, in.size, in.radius);
+    let inner = inner_distance(in.local, in.size, in.radius, in.border);
+    // one pixel in vunits, derivatives are taken before any discard
+    let px = max((abs(dpdx(in.local.x)) + abs(dpdy(in.local.y))) * 0.5, 1e-6);
+    if clipped(in.screen, in.clip) {
+        discard;
+    }
+    // square frames keep the rasterizer's hard edges so neighbouring cells don't show seams
+    var coverage = 1.0;
+    if any(in.radius > vec4<f32>(0.0)) {
+        coverage = clamp(0.5 - outer / px, 0.0, 1.0);
+    }
+    var color = gradient_color(in.gradient, in.local, in.size, in.color);
+    if any(in.border > vec4<f32>(0.0)) {
+        color = mix(border_color(in), color, clamp(0.5 - inner / px, 0.0, 1.0));
+    }
+    if coverage <= 0.0 {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * coverage * in.opacity);
 }
 
 struct VertexIndexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: u32,
+    @location(0) @interpolate(flat) color: u32,
+    @location(1) screen: vec2<f32>,
+    @location(2) @interpolate(flat) clip: vec4<f32>,
+    @location(3) local: vec2<f32>,
+    @location(4) @interpolate(flat) size: vec2<f32>,
+    @location(5) @interpolate(flat) radius: vec4<f32>,
 };
 
 @vertex
@@ -65,18 +273,204 @@ fn vs_index_main(
     v: VertexInput
 ) -> VertexIndexOutput {
     var out: VertexIndexOutput;
-    let xywh = calculate_margin(v.vertex_xywh, v.margin);
-    let abs_pos = vec2<f32>(xywh.xy);
-    let abs_dim = vec2<f32>(xywh.zw);
-    let cam = vec4<f32>(camera_array[v.camera_index].xywh);
-    let rel_pos: vec2<f32> = (abs_pos + cam.xy) / cam.zw;
-    let rel_dim: vec2<f32> = abs_dim / cam.zw;
-    out.clip_position = vec4<f32>( (((v.position / vec2<f32>(2.0, -2.0) + vec2<f32>(0.5, 0.5))  * rel_dim  + rel_pos) * vec2<f32>(2.0,-2.0) - vec2<f32>(1.0, -1.0)), 1.0,1.0);
+    let p = place(v);
+    out.clip_position = p.clip_position;
+    out.screen = p.screen;
+    out.clip = p.clip;
     out.color = v.index;
+    out.local = p.local;
+    out.size = p.size;
+    out.radius = vec4<f32>(v.radius);
     return out;
 }
 
 @fragment
 fn fs_index_main(in: VertexIndexOutput) -> @location(0) u32 {
+    // the area cut off by rounded corners can't be clicked
+    if clipped(in.screen, in.clip) || rounded_box(in.local, in.size, in.radius) > 0.0 {
+        discard;
+    }
     return in.color;
-}
\ No newline at end of file
+}
+
+// Shadows, drawn right before their frame
+
+struct VertexShadowOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) color: vec4<f32>,
+    @location(1) screen: vec2<f32>,
+    @location(2) @interpolate(flat) clip: vec4<f32>,
+    // position inside of the shadow's shape before it is blurred
+    @location(3) local: vec2<f32>,
+    @location(4) @interpolate(flat) size: vec2<f32>,
+    @location(5) @interpolate(flat) radius: vec4<f32>,
+    @location(6) @interpolate(flat) blur: f32,
+    // position inside of the frame's visible bounds, the shadow is cut out under the frame
+    @location(7) frame_local: vec2<f32>,
+    @location(8) @interpolate(flat) frame_size: vec2<f32>,
+    @location(9) @interpolate(flat) frame_radius: vec4<f32>,
+};
+
+@vertex
+fn vs_shadow_main(
+    v: VertexInput
+) -> VertexShadowOutput {
+    var out: VertexShadowOutput;
+    let visible = calculate_margin(v.vertex_xywh, v.margin);
+    let blur = v.shadow.z;
+    let spread = v.shadow.w;
+    let shape = vec4<i32>(
+        visible.xy + v.shadow.xy - vec2<i32>(spread),
+        max(visible.zw + vec2<i32>(2 * spread), vec2<i32>(0)),
+    );
+    let quad = vec4<i32>(shape.xy - vec2<i32>(blur), shape.zw + vec2<i32>(2 * blur));
+    let p = place_rect(v, quad);
+    out.clip_position = p.clip_position;
+    out.screen = p.screen;
+    out.clip = p.clip;
+    let shadow_color = srgb_to_linear(v.shadow_color);
+    out.color = vec4<f32>(shadow_color.rgb, shadow_color.a * v.opacity.x);
+    out.local = p.local - vec2<f32>(f32(blur));
+    out.size = vec2<f32>(shape.zw);
+    // spread grows rounded corners with the shape, square ones stay square
+    out.radius = select(max(vec4<f32>(v.radius + vec4<i32>(spread)), vec4<f32>(0.0)), vec4<f32>(0.0), v.radius == vec4<i32>(0));
+    out.blur = f32(blur);
+    out.frame_local = p.local - vec2<f32>(visible.xy - quad.xy);
+    out.frame_size = vec2<f32>(visible.zw);
+    out.frame_radius = vec4<f32>(v.radius);
+    return out;
+}
+
+/// approximation of the error function, good to about 5e-4
+fn erf(x: f32) -> f32 {
+    let s = sign(x);
+    let a = abs(x);
+    let t = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    let t2 = t * t;
+    return s - s / (t2 * t2);
+}
+
+@fragment
+fn fs_shadow_main(in: VertexShadowOutput) -> @location(0) vec4<f32> {
+    let d = rounded_box(in.local, in.size, in.radius);
+    let frame = rounded_box(in.frame_local, in.frame_size, in.frame_radius);
+    let px = max((abs(dpdx(in.local.x)) + abs(dpdy(in.local.y))) * 0.5, 1e-6);
+    if clipped(in.screen, in.clip) || frame < -px {
+        discard;
+    }
+    // like css the blur radius is twice the standard deviation, an unblurred shadow is anti-aliased
+    let sigma = max(in.blur, px) * 0.5;
+    let coverage = (0.5 - 0.5 * erf(d / (sigma * 1.41421356))) * clamp(0.5 + frame / px, 0.0, 1.0);
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
+
+// Backdrops, what is drawn behind a frame blurred inside of its visible bounds
+
+@group(1) @binding(0)
+var backdrop: texture_2d<f32>;
+@group(1) @binding(1)
+var backdrop_sampler: sampler;
+
+struct VertexBackdropOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(1) screen: vec2<f32>,
+    @location(2) @interpolate(flat) clip: vec4<f32>,
+    @location(3) local: vec2<f32>,
+    @location(4) @interpolate(flat) size: vec2<f32>,
+    @location(5) @interpolate(flat) radius: vec4<f32>,
+    @location(6) @interpolate(flat) blur: f32,
+    @location(7) @interpolate(flat) opacity: f32,
+};
+
+@vertex
+fn vs_backdrop_main(
+    v: VertexInput
+) -> VertexBackdropOutput {
+    var out: VertexBackdropOutput;
+    let p = place(v);
+    out.clip_position = p.clip_position;
+    out.screen = p.screen;
+    out.clip = p.clip;
+    out.local = p.local;
+    out.size = p.size;
+    out.radius = vec4<f32>(v.radius);
+    out.blur = f32(v.backdrop_blur);
+    out.opacity = v.opacity.x;
+    return out;
+}
+
+const BACKDROP_TAPS = 4;
+
+@fragment
+fn fs_backdrop_main(in: VertexBackdropOutput) -> @location(0) vec4<f32> {
+    let outer = rounded_box(in.local, in.size, in.radius);
+    let px = max((abs(dpdx(in.local.x)) + abs(dpdy(in.local.y))) * 0.5, 1e-6);
+    if clipped(in.screen, in.clip) {
+        discard;
+    }
+    var coverage = 1.0;
+    if any(in.radius > vec4<f32>(0.0)) {
+        coverage = clamp(0.5 - outer / px, 0.0, 1.0);
+    }
+    if coverage <= 0.0 {
+        discard;
+    }
+    // gaussian taps spread over the blur radius, the sampler filters between them
+    let dim = vec2<f32>(textureDimensions(backdrop));
+    let uv = in.clip_position.xy / dim;
+    let step = vec2<f32>(in.blur / px / f32(BACKDROP_TAPS)) / dim;
+    var sum = vec4<f32>(0.0);
+    var weight = 0.0;
+    for (var y = -BACKDROP_TAPS; y <= BACKDROP_TAPS; y++) {
+        for (var x = -BACKDROP_TAPS; x <= BACKDROP_TAPS; x++) {
+            let w = exp(-f32(x * x + y * y) / f32(BACKDROP_TAPS * BACKDROP_TAPS / 2));
+            sum += textureSampleLevel(backdrop, backdrop_sampler, uv + vec2<f32>(f32(x), f32(y)) * step, 0.0) * w;
+            weight += w;
+        }
+    }
+    return vec4<f32>(sum.rgb / weight, coverage * in.opacity);
+}
+
+// Opacity groups, a frame and its descendants drawn into their own texture first
+
+struct VertexGroupOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) opacity: f32,
+};
+
+@vertex
+fn vs_group_main(
+    v: VertexInput
+) -> VertexGroupOutput {
+    var out: VertexGroupOutput;
+    // the group's texture covers the whole target
+    out.clip_position = vec4<f32>(v.position, 1.0, 1.0);
+    out.opacity = v.opacity.y;
+    return out;
+}
+
+// the group's texture is bound like a backdrop, its colors are already premultiplied
+@fragment
+fn fs_group_main(in: VertexGroupOutput) -> @location(0) vec4<f32> {
+    return textureLoad(backdrop, vec2<i32>(in.clip_position.xy), 0) * in.opacity;
+}
+
+// Encoding, targets that can't be viewed as srgb are drawn into an srgb texture first
+
+@vertex
+fn vs_encode_main(
+    v: VertexInput
+) -> VertexGroupOutput {
+    var out: VertexGroupOutput;
+    out.clip_position = vec4<f32>(v.position, 1.0, 1.0);
+    out.opacity = 1.0;
+    return out;
+}
+
+// loading the srgb texture decodes it, the target stores what is returned as it is
+@fragment
+fn fs_encode_main(in: VertexGroupOutput) -> @location(0) vec4<f32> {
+    let c = textureLoad(backdrop, vec2<i32>(in.clip_position.xy), 0);
+    let curve = 1.055 * pow(c.rgb, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return vec4<f32>(select(curve, c.rgb * 12.92, c.rgb <= vec3<f32>(0.0031308)), c.a);
+}
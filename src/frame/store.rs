@@ -0,0 +1,634 @@
+use std::ops::Range;
+
+use bytemuck::{bytes_of, Pod, Zeroable};
+use log::warn;
+
+use crate::{
+    color::Color,
+    grid::Direction,
+    manager::{BBox, Borders, Corners},
+    units::{UnitContext, UserUnits, VUnit},
+};
+
+use super::{
+    data::{GradientData, StopData},
+    dirty::DirtyRanges,
+    AspectRatio, Border, FrameData, Gradient, Layer, Shadow,
+};
+
+/// `(draw position, instances, area)` of content drawn on top of a frame,
+/// see `FrameStore::content_breaks`
+pub type ContentSpan = (u32, Range<u32>, BBox);
+
+/// the cpu side of every frame, layout writes here and `FrameRenderer` uploads it
+pub struct FrameStore {
+    data: Vec<FrameData>,
+    changed: DirtyRanges,
+    camera_data: Vec<Camera>,
+    cameras_changed: DirtyRanges,
+    scroll: Vec<[VUnit; 2]>,
+    clip: Vec<bool>,
+    margin: Vec<Option<Borders<UserUnits>>>,
+    border: Vec<Option<Borders<Border>>>,
+    radius: Vec<Option<Corners<UserUnits>>>,
+    shadow: Vec<Option<Shadow>>,
+    backdrop_blur: Vec<Option<UserUnits>>,
+    aspect: Vec<Option<AspectRatio>>,
+    /// set for grid members, other frames follow `direction`
+    rtl: Vec<Option<bool>>,
+    gradient: Vec<Option<Gradient>>,
+    /// set when the gradient buffers have to be rebuilt
+    gradients_changed: bool,
+    z_index: Vec<i32>,
+    layer: Vec<Option<Layer>>,
+    /// frame indices in the order they are drawn and hit tested
+    order: Vec<u32>,
+    /// draw position of every frame, the inverse of `order`
+    position: Vec<u32>,
+    /// the frame's opacity and if it applies to its descendants as one group
+    opacity: Vec<(f32, bool)>,
+    /// draw positions of every opacity group's frame and its descendants in the same layer
+    groups: Vec<Range<u32>>,
+    order_changed: bool,
+    rem: VUnit,
+    direction: Direction,
+}
+#[derive(Pod, Zeroable, Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Camera {
+    bbox: BBox,
+    /// scroll offset of this frame and all of its ancestors, applied to its children
+    offset: [VUnit; 2],
+    _pad: [VUnit; 2],
+    /// screen space rect everything drawn with this camera is clipped to
+    clip: BBox,
+}
+
+impl Default for FrameStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameStore {
+    /// camera of floating frames, it always matches the root frame without any scroll offset
+    pub const WINDOW_CAMERA: u32 = 0;
+    /// content areas waiting for a frame that covers them before they are drawn anyway
+    const MAX_PENDING: usize = 64;
+    /// camera the children of the frame at `index` are drawn with
+    pub const fn camera(index: usize) -> u32 {
+        index as u32 + 1
+    }
+    pub fn new() -> Self {
+        Self {
+            data: vec![],
+            changed: DirtyRanges::default(),
+            camera_data: vec![Camera::zeroed()],
+            cameras_changed: DirtyRanges::default(),
+            scroll: vec![],
+            clip: vec![],
+            margin: vec![],
+            aspect: vec![],
+            border: vec![],
+            radius: vec![],
+            shadow: vec![],
+            backdrop_blur: vec![],
+            rtl: vec![],
+            gradient: vec![],
+            gradients_changed: false,
+            z_index: vec![],
+            layer: vec![],
+            order: vec![],
+            position: vec![],
+            opacity: vec![],
+            groups: vec![],
+            order_changed: false,
+            direction: Direction::Ltr,
+            rem: UnitContext::DEFAULT_REM.into(),
+        }
+    }
+    /// resolves the scroll offsets and clip rects of every camera
+    pub fn solve_cameras(&mut self) {
+        // parents are always added before their children
+        for i in 0..self.data.len() {
+            let parent = self.data[i].camera_index as usize;
+            let [x, y] = self.scroll[i];
+            let Camera {
+                offset: [px, py],
+                clip: parent_clip,
+                ..
+            } = self.camera_data[parent];
+            let offset = [x + px, y + py];
+            let clip = match self.clip[i] {
+                true => {
+                    let mut visible = self.data[i].visible_bounds();
+                    visible.x = visible.x - px;
+                    visible.y = visible.y - py;
+                    visible.intersect(&parent_clip)
+                }
+                false => parent_clip,
+            };
+            let camera = &mut self.camera_data[Self::camera(i) as usize];
+            let old = (camera.offset, camera.clip);
+            camera.offset = offset;
+            camera.clip = clip;
+            if bytes_of(&old.0) != bytes_of(&offset) || bytes_of(&old.1) != bytes_of(&clip) {
+                self.cameras_changed.mark(Self::camera(i) as usize);
+            }
+        }
+    }
+    /// sorts the frames by layer, then every frame after its parent
+    /// and siblings by their z index and creation order, and finds the opacity groups
+    pub fn solve_order(&mut self) {
+        if !self.order_changed {
+            return;
+        }
+        self.order_changed = false;
+        let mut keys: Vec<(Layer, Vec<(i32, u32)>)> = Vec::with_capacity(self.data.len());
+        for i in 0..self.data.len() {
+            // floating frames are children of the root frame
+            let (layer, mut path) = match (i, self.data[i].camera_index) {
+                (0, _) => (Layer::Base, vec![]),
+                (_, Self::WINDOW_CAMERA) => keys[0].clone(),
+                (_, camera) => keys[camera as usize - 1].clone(),
+            };
+            path.push((self.z_index[i], i as u32));
+            keys.push((self.layer[i].unwrap_or(layer), path));
+        }
+        self.order = (0..self.data.len() as u32).collect();
+        self.order.sort_by(|&a, &b| keys[a as usize].cmp(&keys[b as usize]));
+        self.position = vec![0; self.order.len()];
+        for (position, &index) in self.order.iter().enumerate() {
+            self.position[index as usize] = position as u32;
+        }
+        // descendants in the same layer follow their ancestor without a gap
+        self.groups.clear();
+        for (position, &index) in self.order.iter().enumerate() {
+            let (opacity, group) = self.opacity[index as usize];
+            if !group || opacity >= 1.0 {
+                continue;
+            }
+            let (layer, path) = &keys[index as usize];
+            let len = self.order[position + 1..]
+                .iter()
+                .take_while(|&&i| {
+                    let (l, p) = &keys[i as usize];
+                    l == layer && p.starts_with(path)
+                })
+                .count();
+            self.groups.push(position as u32..(position + 1 + len) as u32);
+        }
+    }
+    /// draw positions of every opacity group in order of their start, as of the last `solve_order`
+    pub fn opacity_groups(&self) -> &[Range<u32>] {
+        &self.groups
+    }
+    /// frame indices in draw order as of the last `solve_order`
+    pub fn draw_order(&self) -> &[u32] {
+        &self.order
+    }
+    /// where the frame at `index` is in `draw_order`, frames added since are ranked by their index
+    pub fn draw_position(&self, index: usize) -> u32 {
+        self.position.get(index).copied().unwrap_or(index as u32)
+    }
+    pub fn data(&self) -> &[FrameData] {
+        &self.data
+    }
+    pub fn cameras(&self) -> &[Camera] {
+        &self.camera_data
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    /// whether anything changed since the changes were last taken
+    pub fn has_changes(&self) -> bool {
+        !self.changed.is_empty()
+            || !self.cameras_changed.is_empty()
+            || self.order_changed
+            || self.gradients_changed
+    }
+    /// sorted ranges of the frames changed since the last call
+    pub fn take_changed(&mut self) -> Vec<Range<usize>> {
+        self.changed.take()
+    }
+    /// sorted ranges of the cameras changed since the last call
+    pub fn take_changed_cameras(&mut self) -> Vec<Range<usize>> {
+        self.cameras_changed.take()
+    }
+    pub fn add(&mut self, frame: FrameData) -> usize {
+        self.camera_data.push(Camera {
+            bbox: frame.data,
+            offset: [0.into(); 2],
+            _pad: [0.into(); 2],
+            clip: frame.data,
+        });
+        self.scroll.push([0.into(); 2]);
+        self.clip.push(false);
+        self.margin.push(None);
+        self.border.push(None);
+        self.radius.push(None);
+        self.shadow.push(None);
+        self.backdrop_blur.push(None);
+        self.aspect.push(None);
+        self.rtl.push(None);
+        self.gradient.push(None);
+        self.z_index.push(0);
+        self.layer.push(None);
+        self.opacity.push((1.0, false));
+        self.order_changed = true;
+        self.data.push(FrameData {
+            opacity: [1.0; 2],
+            ..frame
+        });
+        self.changed.mark(self.data.len() - 1);
+        self.cameras_changed.mark(self.camera_data.len() - 1);
+        self.data.len() - 1
+    }
+    /// `bounds` is the space the frame is given, an aspect ratio can shrink it
+    pub fn update(&mut self, index: usize, bounds: &BBox) {
+        let bounds = &match &self.aspect[index] {
+            Some(aspect) => aspect.fit(bounds),
+            None => *bounds,
+        };
+        // layout updates every frame on each solve, only the ones that moved are uploaded
+        let old = self.data[index];
+        self.data[index].data = *bounds;
+        self.solve_units(index);
+        if bytes_of(&old) == bytes_of(&self.data[index]) {
+            return;
+        }
+        self.camera_data[Self::camera(index) as usize].bbox = *bounds;
+        self.cameras_changed.mark(Self::camera(index) as usize);
+        if index == 0 {
+            let window = &mut self.camera_data[Self::WINDOW_CAMERA as usize];
+            window.bbox = *bounds;
+            window.clip = *bounds;
+            self.cameras_changed.mark(Self::WINDOW_CAMERA as usize);
+        }
+        self.changed.mark(index);
+    }
+    pub fn update_color(&mut self, index: usize, color: Color) {
+        let frame = &mut self.data[index];
+        if frame.color != color {
+            frame.color = color;
+            self.changed.mark(index);
+        }
+    }
+    /// marks the frame at `index` when solving its units changed it from `old`
+    fn mark_solved(&mut self, index: usize, old: FrameData) {
+        self.solve_units(index);
+        if bytes_of(&old) != bytes_of(&self.data[index]) {
+            self.changed.mark(index);
+        }
+    }
+    /// resolves the margin, border, radius, shadow and backdrop blur against the frame's bounds
+    fn solve_units(&mut self, index: usize) {
+        let ctx = self.unit_context();
+        let rtl = self.is_rtl(index);
+        let frame = &mut self.data[index];
+        let bounds = frame.data;
+        let side = bounds.w.min(bounds.h);
+        if let Some(margin) = &self.margin[index] {
+            frame.margin = margin.solve(&bounds, rtl, &ctx);
+        }
+        if let Some(border) = &self.border[index] {
+            let widths = Borders {
+                top: border.top.width.clone(),
+                bottom: border.bottom.width.clone(),
+                left: border.left.width.clone(),
+                right: border.right.width.clone(),
+            };
+            frame.border = widths.solve(&bounds, rtl, &ctx);
+            // colors follow their strokes when `rtl` swaps them
+            let (left, right) = match rtl {
+                true => (&border.right, &border.left),
+                false => (&border.left, &border.right),
+            };
+            frame.border_color = [border.top.color, border.bottom.color, left.color, right.color];
+        }
+        if let Some(radius) = &self.radius[index] {
+            frame.radius = radius.solve(&bounds, &ctx);
+        }
+        if let Some(shadow) = &self.shadow[index] {
+            let [x, y] = &shadow.offset;
+            frame.shadow = [
+                x.solve(bounds.w, &ctx),
+                y.solve(bounds.h, &ctx),
+                shadow.blur.solve(side, &ctx).max(0.into()),
+                shadow.spread.solve(side, &ctx),
+            ];
+            frame.shadow_color = shadow.color;
+        }
+        if let Some(blur) = &self.backdrop_blur[index] {
+            frame.backdrop_blur = blur.solve(side, &ctx).max(0.into());
+        }
+    }
+    pub fn update_margin(&mut self, index: usize, margin: Borders<UserUnits>) {
+        self.margin[index] = Some(margin);
+        self.mark_solved(index, self.data[index]);
+    }
+    /// relative to the smaller side of the frame
+    pub fn update_radius(&mut self, index: usize, radius: Corners<UserUnits>) {
+        self.radius[index] = Some(radius);
+        self.mark_solved(index, self.data[index]);
+    }
+    pub fn update_border(&mut self, index: usize, border: Borders<Border>) {
+        self.border[index] = Some(border);
+        self.mark_solved(index, self.data[index]);
+    }
+    pub fn update_shadow(&mut self, index: usize, shadow: Shadow) {
+        self.shadow[index] = Some(shadow);
+        self.mark_solved(index, self.data[index]);
+    }
+    /// relative to the smaller side of the frame
+    pub fn update_backdrop_blur(&mut self, index: usize, blur: UserUnits) {
+        self.backdrop_blur[index] = Some(blur);
+        self.mark_solved(index, self.data[index]);
+    }
+    pub fn update_gradient(&mut self, index: usize, gradient: Gradient) {
+        self.gradient[index] = match gradient.stops().is_empty() {
+            true => None,
+            false => Some(gradient),
+        };
+        self.gradients_changed = true;
+    }
+    /// every gradient and its stops when one changed since the last call,
+    /// points each frame at its gradient
+    pub fn take_gradients(&mut self) -> Option<(Vec<GradientData>, Vec<StopData>)> {
+        if !std::mem::take(&mut self.gradients_changed) {
+            return None;
+        }
+        let mut gradients = vec![];
+        let mut stops = vec![];
+        for (index, (frame, gradient)) in self.data.iter_mut().zip(&self.gradient).enumerate() {
+            let old = frame.gradient;
+            frame.gradient = match gradient {
+                Some(g) => {
+                    gradients.push(g.data(stops.len(), &mut stops));
+                    gradients.len() as u32
+                }
+                None => 0,
+            };
+            if frame.gradient != old {
+                self.changed.mark(index);
+            }
+        }
+        Some((gradients, stops))
+    }
+    /// `opacity` and `group` keep their value when `None`
+    pub fn update_opacity(&mut self, index: usize, opacity: Option<f32>, group: Option<bool>) {
+        let (was_opacity, was_group) = self.opacity[index];
+        let opacity = opacity.unwrap_or(was_opacity).clamp(0.0, 1.0);
+        let group = group.unwrap_or(was_group);
+        if (opacity, group) == (was_opacity, was_group) {
+            return;
+        }
+        self.opacity[index] = (opacity, group);
+        self.data[index].opacity = match group {
+            true => [1.0, opacity],
+            false => [opacity, 1.0],
+        };
+        if group || was_group {
+            self.order_changed = true;
+        }
+        self.changed.mark(index);
+    }
+    /// draw order among siblings in the same layer, applied on the next `solve_order`
+    pub fn update_z_index(&mut self, index: usize, z_index: i32) {
+        if self.z_index[index] != z_index {
+            self.z_index[index] = z_index;
+            self.order_changed = true;
+        }
+    }
+    /// draws the frame and descendants without a layer of their own in `layer`, like `z_index`
+    pub fn update_layer(&mut self, index: usize, layer: Layer) {
+        if self.layer[index] != Some(layer) {
+            self.layer[index] = Some(layer);
+            self.order_changed = true;
+        }
+    }
+    /// applied on the next `update` of the frame
+    pub fn update_aspect(&mut self, index: usize, aspect: AspectRatio) {
+        if index == 0 {
+            warn!("the root frame always fills the window, its aspect ratio is ignored");
+            return;
+        }
+        let aspect = match aspect.ratio > 0.0 {
+            true => Some(aspect),
+            false => None,
+        };
+        if self.aspect[index] != aspect {
+            self.aspect[index] = aspect;
+            // the frame is refit by the next solve
+            self.changed.mark(index);
+        }
+    }
+    pub fn update_clip(&mut self, index: usize, clip: bool) {
+        if self.clip[index] != clip {
+            self.clip[index] = clip;
+            self.cameras_changed.mark(Self::camera(index) as usize);
+        }
+    }
+    /// the root frame is the viewport, it is resized with the window
+    pub fn unit_context(&self) -> UnitContext {
+        let viewport = self.data.first().map_or([0.into(); 2], |f| [f.data.w, f.data.h]);
+        UnitContext {
+            viewport,
+            rem: self.rem,
+        }
+    }
+    /// direction of grids that don't set their own
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+    /// every frame is uploaded again when it changes, its margins and borders flip
+    pub fn set_direction(&mut self, direction: Direction) {
+        if self.direction != direction {
+            self.direction = direction;
+            self.changed.mark_range(0..self.data.len());
+        }
+    }
+    /// swaps the left and right margins and borders of the frame from its next `update`
+    pub fn update_rtl(&mut self, index: usize, rtl: bool) {
+        self.rtl[index] = Some(rtl);
+    }
+    /// grid members follow their grid, other frames the window's direction
+    pub fn is_rtl(&self, index: usize) -> bool {
+        self.rtl[index].unwrap_or(self.direction == Direction::Rtl)
+    }
+    /// every frame is uploaded again when it changes, any of their units could be rems
+    pub fn set_rem(&mut self, rem: VUnit) {
+        if bytes_of(&self.rem) != bytes_of(&rem) {
+            self.rem = rem;
+            self.changed.mark_range(0..self.data.len());
+        }
+    }
+    pub fn get(&self, index: usize) -> &FrameData {
+        &self.data[index]
+    }
+    /// offsets the children of the frame at `index`
+    pub fn set_scroll(&mut self, index: usize, offset: [VUnit; 2]) {
+        self.scroll[index] = offset;
+    }
+    /// visible bounds of the frame at `index` after the scroll offsets of its ancestors
+    pub fn screen_bounds(&self, index: usize) -> BBox {
+        self.to_screen(self.data[index].camera_index, self.data[index].visible_bounds())
+    }
+    /// moves `bounds` drawn with `camera` by the scroll offsets of the camera's frame and its ancestors
+    pub fn to_screen(&self, mut camera: u32, mut bounds: BBox) -> BBox {
+        while camera != Self::WINDOW_CAMERA {
+            let parent = camera as usize - 1;
+            let [x, y] = self.scroll[parent];
+            bounds.x = bounds.x - x;
+            bounds.y = bounds.y - y;
+            camera = self.data[parent].camera_index;
+        }
+        bounds
+    }
+    /// screen space clip rect of `camera` as of the last `solve_cameras`
+    pub fn clip_rect(&self, camera: u32) -> BBox {
+        self.camera_data[camera as usize].clip
+    }
+    /// `screen_bounds` clipped by the frame's camera, empty when the frame is hidden
+    pub fn clipped_bounds(&self, index: usize) -> BBox {
+        self.clip_rect(self.data[index].camera_index)
+            .intersect(&self.screen_bounds(index))
+    }
+    /// content drawn on top of frames, like text, is batched until a later frame covers it,
+    /// `content` is in draw order,
+    /// returns the instances to draw right before each draw position, `len()` for after the last one
+    pub fn content_breaks(&self, content: &[ContentSpan]) -> Vec<(u32, Range<u32>)> {
+        let mut res = vec![];
+        let mut pending: Vec<BBox> = vec![];
+        let mut instances = 0..0;
+        let mut content = content.iter().peekable();
+        // content is never batched across the edge of an opacity group
+        let mut edges = vec![false; self.len() + 1];
+        for group in &self.groups {
+            edges[group.start as usize] = true;
+            edges[group.end as usize] = true;
+        }
+        for (position, &index) in self.order.iter().enumerate() {
+            let bounds = self.screen_bounds(index as usize);
+            let covered = edges[position]
+                || pending.len() >= Self::MAX_PENDING
+                || pending.iter().any(|area| {
+                    let i = area.intersect(&bounds);
+                    i.w > 0.into() && i.h > 0.into()
+                });
+            let position = position as u32;
+            if covered && !pending.is_empty() {
+                res.push((position, instances.clone()));
+                pending.clear();
+                instances = instances.end..instances.end;
+            }
+            if let Some((_, range, area)) = content.next_if(|c| c.0 == position) {
+                pending.push(*area);
+                instances.end = range.end;
+            }
+        }
+        if !pending.is_empty() {
+            res.push((self.len() as u32, instances));
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox([x, y, w, h]: [i32; 4]) -> BBox {
+        BBox {
+            x: x.into(),
+            y: y.into(),
+            w: w.into(),
+            h: h.into(),
+        }
+    }
+    fn pix(b: BBox) -> [f32; 4] {
+        [b.x, b.y, b.w, b.h].map(|v| v.pix())
+    }
+    /// adds a frame drawn with `camera` at `bounds`
+    fn frame(store: &mut FrameStore, camera: u32, bounds: [i32; 4]) -> usize {
+        let index = store.add(FrameData {
+            camera_index: camera,
+            ..FrameData::zeroed()
+        });
+        store.update(index, &bbox(bounds));
+        index
+    }
+
+    #[test]
+    fn nested_clips_intersect_their_ancestors() {
+        let mut store = FrameStore::new();
+        let root = frame(&mut store, FrameStore::WINDOW_CAMERA, [0, 0, 200, 200]);
+        let grandparent = frame(&mut store, FrameStore::camera(root), [20, 20, 100, 100]);
+        let parent = frame(&mut store, FrameStore::camera(grandparent), [30, 30, 50, 50]);
+        let child = frame(&mut store, FrameStore::camera(parent), [60, 40, 60, 60]);
+        store.update_clip(grandparent, true);
+        store.update_clip(parent, true);
+        store.set_scroll(grandparent, [0.into(), 30.into()]);
+        store.solve_cameras();
+        assert_eq!(pix(store.screen_bounds(parent)), [30.0, 0.0, 50.0, 50.0]);
+        let ancestors = [root, grandparent, parent]
+            .map(|i| store.screen_bounds(i))
+            .into_iter()
+            .reduce(|a, b| a.intersect(&b))
+            .unwrap();
+        let clip = store.clip_rect(store.get(child).camera_index);
+        assert_eq!(pix(clip), pix(ancestors));
+        assert_eq!(pix(clip), [30.0, 20.0, 50.0, 30.0]);
+        assert_eq!(pix(store.clipped_bounds(child)), [60.0, 20.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn layers_then_z_index_then_creation_order() {
+        let mut store = FrameStore::new();
+        let bounds = [0, 0, 10, 10];
+        let root = frame(&mut store, FrameStore::WINDOW_CAMERA, bounds);
+        let floating = frame(&mut store, FrameStore::WINDOW_CAMERA, bounds);
+        let below = frame(&mut store, FrameStore::camera(root), bounds);
+        let equal = frame(&mut store, FrameStore::camera(root), bounds);
+        let popup = frame(&mut store, FrameStore::camera(root), bounds);
+        let popup_child = frame(&mut store, FrameStore::camera(popup), bounds);
+        let above = frame(&mut store, FrameStore::camera(root), bounds);
+        store.update_z_index(below, -1);
+        store.update_z_index(popup, -5);
+        store.update_layer(popup, Layer::Popup);
+        store.update_z_index(above, 10);
+        store.solve_order();
+        let order = [root, below, floating, equal, above, popup, popup_child];
+        assert_eq!(store.draw_order(), order.map(|i| i as u32));
+        for (position, index) in order.into_iter().enumerate() {
+            assert_eq!(store.draw_position(index), position as u32);
+        }
+    }
+
+    #[test]
+    fn group_opacity_collects_descendants() {
+        let mut store = FrameStore::new();
+        let bounds = [0, 0, 10, 10];
+        let root = frame(&mut store, FrameStore::WINDOW_CAMERA, bounds);
+        let group = frame(&mut store, FrameStore::camera(root), bounds);
+        let child = frame(&mut store, FrameStore::camera(group), bounds);
+        frame(&mut store, FrameStore::camera(child), bounds);
+        let popup = frame(&mut store, FrameStore::camera(group), bounds);
+        let own = frame(&mut store, FrameStore::camera(root), bounds);
+        frame(&mut store, FrameStore::camera(own), bounds);
+        store.update_opacity(group, Some(0.5), Some(true));
+        store.update_opacity(own, Some(0.5), Some(false));
+        // a child in another layer is drawn apart from the group
+        store.update_layer(popup, Layer::Popup);
+        store.solve_order();
+        assert_eq!(store.draw_order(), [0, 1, 2, 3, 5, 6, 4]);
+        assert_eq!(store.opacity_groups(), [1..4]);
+        // an opaque group draws like any other frame
+        store.update_opacity(group, Some(1.0), None);
+        store.solve_order();
+        assert!(store.opacity_groups().is_empty());
+    }
+}
@@ -1,9 +1,10 @@
 pub(super) mod builder;
 pub(super) mod data;
 pub(super) mod renderer;
+pub(super) mod template;
 
 pub(crate) use builder::GridSpacer;
 
-pub use builder::{GridBuilder, SpacerUnit, XName, YName};
+pub use builder::{Direction, GridBuilder, SpacerUnit, XName, YName};
 
 pub use renderer::{GridHandle, GridRenderer};
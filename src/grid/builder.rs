@@ -1,14 +1,18 @@
-use std::marker::PhantomData;
+use std::{iter, marker::PhantomData};
 
 use crate::{
+    error::{TemplateError, TemplateErrorKind},
     frame::FrameHandle,
     handle::{FallableHandleLike, Handle, HandleLike},
-    units::UserUnits,
+    units::{Pixelt, UserUnits, VUnit},
 };
 
-use super::data::{GridData, GridExpandDir};
+use super::{
+    data::{GridData, GridExpandDir},
+    template,
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SpacerUnit {
     Unit(UserUnits),
     Repeat(UserUnits),
@@ -52,24 +56,41 @@ impl GridDir for Option<YName> {
 
 pub(crate) type GridSpacer = Vec<SpacerUnit>;
 
+/// horizontal order of a grid's columns
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
 #[derive(Debug)]
 pub struct GridBuilder {
     spacers: [GridSpacer; 2],
+    /// minimum parent width in pixels and the tracks that replace the base ones from there
+    breakpoints: Vec<(Pixelt, [Option<GridSpacer>; 2])>,
     expands: Option<GridExpandDir>,
+    direction: Option<Direction>,
     parent: FrameHandle,
 }
 
 pub struct SpacerBuilder<'b, const EXPANDS: bool, T: GridDir + FallableHandleLike> {
     grid_builder: &'b mut GridBuilder,
     spacer: GridSpacer,
+    /// set when a template added an auto-fill track
+    expanding: bool,
+    /// index into `GridBuilder::breakpoints`, `None` builds the base tracks
+    breakpoint: Option<usize>,
     _dir: PhantomData<T>,
 }
 
 impl<'b, const EXPANDS: bool, T: GridDir + FallableHandleLike> SpacerBuilder<'b, EXPANDS, T> {
-    fn new(grid_builder: &'b mut GridBuilder) -> Self {
+    fn new(grid_builder: &'b mut GridBuilder, breakpoint: Option<usize>) -> Self {
         Self {
             grid_builder: grid_builder,
             spacer: GridSpacer::new(),
+            expanding: false,
+            breakpoint,
             _dir: PhantomData,
         }
     }
@@ -78,11 +99,49 @@ impl<'b, const EXPANDS: bool, T: GridDir + FallableHandleLike> SpacerBuilder<'b,
         self
     }
 
+    /// appends the tracks of a template like `"200px repeat(2, 1fr) 25%"`,
+    /// `auto-fill` or `repeat(auto-fill, <unit>)` adds an expanding track
+    pub fn template(mut self, template: &str) -> Result<Self, TemplateError> {
+        let spacer = template::parse(template)?;
+        let expands = spacer.iter().any(|s| matches!(s, SpacerUnit::Repeat(_)));
+        if expands && (EXPANDS || self.expanding) {
+            let pos = template.find("auto-fill").unwrap_or(0);
+            return Err(TemplateError {
+                pos,
+                kind: TemplateErrorKind::MultipleAutoFill,
+            });
+        }
+        if expands && self.grid_builder.expands.is_some_and(|d| d != T::dir()) {
+            return Err(TemplateError {
+                pos: template.find("auto-fill").unwrap_or(0),
+                kind: TemplateErrorKind::ExpandsBothDirections,
+            });
+        }
+        self.expanding |= expands;
+        self.spacer.extend(spacer);
+        Ok(self)
+    }
+
+    /// panics when `add_expanding` was used after the other direction already expands,
+    /// `template` reports that as an error instead
     pub fn build(self) {
-        self.grid_builder.spacers[match T::dir() {
+        // auto-fill from a template expands the grid just like `add_expanding`
+        if EXPANDS || self.expanding {
+            self.grid_builder.expands = match self.grid_builder.expands {
+                Some(dir) if dir != T::dir() => {
+                    panic!("grid's can only have one expanding direction")
+                }
+                _ => Some(T::dir()),
+            };
+        }
+        let dir = match T::dir() {
             GridExpandDir::X => 0,
             _ => 1,
-        }] = self.spacer;
+        };
+        match self.breakpoint {
+            Some(i) => self.grid_builder.breakpoints[i].1[dir] = Some(self.spacer),
+            None => self.grid_builder.spacers[dir] = self.spacer,
+        }
     }
 
     pub fn assign<const N: usize>(self) -> [T; N] {
@@ -90,13 +149,6 @@ impl<'b, const EXPANDS: bool, T: GridDir + FallableHandleLike> SpacerBuilder<'b,
         for (i, (var, _)) in res.iter_mut().zip(self.spacer.iter()).enumerate() {
             *var = T::new(Some(i));
         }
-        self.grid_builder.expands = match &self.grid_builder.expands {
-            None => Some(T::dir()),
-            _ if EXPANDS => {
-                panic!("grid's can only have one expanding direction")
-            }
-            n => *n,
-        };
         self.build();
         res
     }
@@ -104,15 +156,33 @@ impl<'b, const EXPANDS: bool, T: GridDir + FallableHandleLike> SpacerBuilder<'b,
 
 impl<'b, T: GridDir + FallableHandleLike> SpacerBuilder<'b, false, T> {
     pub fn add_expanding(mut self: Self, u: UserUnits) -> SpacerBuilder<'b, true, T> {
+        assert!(!self.expanding, "a grid direction can only have one expanding track");
         self.spacer.push(SpacerUnit::Repeat(u.clone()));
         SpacerBuilder {
             spacer: self.spacer,
             grid_builder: self.grid_builder,
+            expanding: self.expanding,
+            breakpoint: self.breakpoint,
             _dir: PhantomData,
         }
     }
 }
 
+/// tracks used while the grid's parent frame is at least `min_width` wide
+pub struct Breakpoint<'b> {
+    grid_builder: &'b mut GridBuilder,
+    index: usize,
+}
+
+impl<'b> Breakpoint<'b> {
+    pub fn widths(self) -> WidthSpacerBuilder<'b, false> {
+        WidthSpacerBuilder::new(self.grid_builder, Some(self.index))
+    }
+    pub fn heights(self) -> HeightSpacerBuilder<'b, false> {
+        HeightSpacerBuilder::new(self.grid_builder, Some(self.index))
+    }
+}
+
 pub type WidthSpacerBuilder<'a, const EXPANDS: bool> = SpacerBuilder<'a, EXPANDS, Option<XName>>;
 pub type HeightSpacerBuilder<'a, const EXPANDS: bool> = SpacerBuilder<'a, EXPANDS, Option<YName>>;
 
@@ -120,21 +190,62 @@ impl GridBuilder {
     pub(crate) fn new(parent: FrameHandle) -> GridBuilder {
         GridBuilder {
             spacers: [GridSpacer::new(), GridSpacer::new()],
+            breakpoints: vec![],
             expands: None,
+            direction: None,
             parent,
         }
     }
+    /// grids without a direction follow the window's, see `UpdateMsg::Direction`
+    pub fn direction(&mut self, direction: Direction) {
+        self.direction = Some(direction);
+    }
     pub(crate) fn parent(&self) -> FrameHandle {
         self.parent
     }
+    /// most columns and rows of any breakpoint, `None` for an auto-fill direction
+    pub(crate) fn track_limits(&self) -> [Option<usize>; 2] {
+        super::data::track_limits(&self.templates())
+    }
     pub fn widths(&mut self) -> WidthSpacerBuilder<false> {
-        WidthSpacerBuilder::new(self)
+        WidthSpacerBuilder::new(self, None)
     }
     pub fn heights(&mut self) -> HeightSpacerBuilder<false> {
-        HeightSpacerBuilder::new(self)
+        HeightSpacerBuilder::new(self, None)
+    }
+    /// directions the breakpoint leaves unset keep the base tracks,
+    /// frames are placed again whenever the breakpoint in use changes
+    pub fn breakpoint(&mut self, min_width: Pixelt) -> Breakpoint<'_> {
+        let index = match self.breakpoints.iter().position(|(w, _)| *w == min_width) {
+            Some(i) => i,
+            None => {
+                self.breakpoints.push((min_width, [None, None]));
+                self.breakpoints.len() - 1
+            }
+        };
+        Breakpoint {
+            grid_builder: self,
+            index,
+        }
+    }
+    /// the base tracks followed by every breakpoint from narrowest to widest
+    fn templates(&self) -> Vec<(VUnit, [GridSpacer; 2])> {
+        let mut breakpoints = self.breakpoints.clone();
+        breakpoints.sort_by_key(|(w, _)| *w);
+        iter::once((0.into(), self.spacers.clone()))
+            .chain(breakpoints.into_iter().map(|(w, [x, y])| {
+                let x = x.unwrap_or_else(|| self.spacers[0].clone());
+                let y = y.unwrap_or_else(|| self.spacers[1].clone());
+                (w.into(), [x, y])
+            }))
+            .collect()
     }
     pub fn build(self) -> GridData {
-        let [x_spacer, y_spacer] = self.spacers;
-        GridData::new(self.parent, x_spacer, y_spacer, self.expands)
+        GridData::new(
+            self.parent,
+            self.templates(),
+            self.expands,
+            self.direction,
+        )
     }
 }
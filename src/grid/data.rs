@@ -4,20 +4,23 @@ use std::{
     vec,
 };
 
+use log::warn;
+
 use crate::{
-    frame::{FrameHandle, FrameRenderer},
+    error::LayoutError,
+    frame::{FrameHandle, FrameStore},
     handle::{FallableHandleLike, HandleLike},
     manager::BBox,
-    units::{Fractiont, UserUnits, VUnit},
+    units::{units_solve, SolveUnits, UnitContext, VUnit},
 };
 
 use crate::grid::GridSpacer;
 
-use crate::grid::{XName, YName};
+use crate::grid::{Direction, XName, YName};
 
 use super::SpacerUnit;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GridExpandDir {
     X,
     Y,
@@ -31,11 +34,19 @@ struct HandleSpacerLocation {
 
 pub struct GridData {
     handles: Vec<HandleSpacerLocation>,
+    /// every frame in the order it was added with the cell it asked for
+    placements: Vec<(FrameHandle, Option<usize>, Option<usize>)>,
+    /// minimum parent width and x and y tracks, the base tracks come first
+    templates: Vec<(VUnit, [GridSpacer; 2])>,
+    active: usize,
     cross_spacer: GridSpacer,
     major_spacer: GridSpacer,
     expand_dir: Option<GridExpandDir>,
     parent_frame_handle: FrameHandle,
     major_row_counts: Vec<usize>,
+    content: [VUnit; 2],
+    solved: Vec<BBox>,
+    direction: Option<Direction>,
 }
 
 #[derive(Clone)]
@@ -45,28 +56,13 @@ pub struct SpacerSolved {
     pub count: usize,
 }
 
-#[derive(Clone)]
-enum SolveUnits {
-    Exact(VUnit),
-    Fraction(Fractiont),
-}
-
-fn units_solve(u: UserUnits, len: VUnit) -> SolveUnits {
-    use SolveUnits::*;
-    match u {
-        UserUnits::Pixel(p) => Exact(p.into()),
-        UserUnits::Ratio(f) => Exact(((len.pix() * f).round() as i32).into()),
-        UserUnits::Zero => Exact(0.into()),
-        UserUnits::Fraction(f) => Fraction(f),
-    }
-}
-
 fn solve_spacer<'a>(
     items: impl Iterator<Item = &'a HandleSpacerLocation> + Clone + 'a,
     spacer_template: &'a GridSpacer,
     which: impl Fn(&HandleSpacerLocation) -> usize + Clone + 'a,
     pos: VUnit,
     len: VUnit,
+    ctx: UnitContext,
 ) -> impl Iterator<Item = SpacerSolved> + 'a {
     let iter_res = spacer_template
         .iter()
@@ -84,7 +80,7 @@ fn solve_spacer<'a>(
                 },
             )
         })
-        .map(move |(i, u)| (i, units_solve(*u, len)));
+        .map(move |(i, u)| (i, units_solve(u, len, &ctx)));
     //debug!("count b4 {}", iter_res.clone().count());
     let (total_f, taken_u) = iter_res
         .clone()
@@ -113,13 +109,36 @@ fn solve_spacer<'a>(
     iter_res
 }
 
+/// most columns and rows of any template, `None` for a direction with an auto-fill track
+/// since it has as many tracks as it needs
+pub(crate) fn track_limits(templates: &[(VUnit, [GridSpacer; 2])]) -> [Option<usize>; 2] {
+    [0, 1].map(|dir| {
+        let spacers = templates.iter().map(|(_, spacers)| &spacers[dir]);
+        let expands = spacers
+            .clone()
+            .any(|s| s.iter().any(|u| matches!(u, SpacerUnit::Repeat(_))));
+        (!expands).then(|| spacers.map(|s| s.len()).max().unwrap_or(0))
+    })
+}
+
+/// the track `index` ends up in, indices past the end go to the auto-fill track,
+/// `None` when there is none
+fn clamp_track(spacer: &GridSpacer, index: usize) -> Option<usize> {
+    let repeat = spacer.iter().rposition(|u| matches!(u, SpacerUnit::Repeat(_)));
+    match repeat {
+        Some(repeat) if index >= spacer.len() => Some(repeat),
+        _ => (index < spacer.len()).then_some(index),
+    }
+}
+
 impl GridData {
     pub fn new(
         parent_frame_handle: FrameHandle,
-        x_spacer: GridSpacer,
-        y_spacer: GridSpacer,
+        templates: Vec<(VUnit, [GridSpacer; 2])>,
         expand_dir: Option<GridExpandDir>,
+        direction: Option<Direction>,
     ) -> Self {
+        let [x_spacer, y_spacer] = templates[0].1.clone();
         let (major_spacer, cross_spacer) = match expand_dir {
             Some(GridExpandDir::X) => (x_spacer, y_spacer),
             _ => (y_spacer, x_spacer),
@@ -129,15 +148,82 @@ impl GridData {
             cross_spacer,
             expand_dir,
             handles: vec![],
+            placements: vec![],
+            templates,
+            active: 0,
             major_row_counts: vec![],
             parent_frame_handle,
+            content: [0.into(); 2],
+            solved: vec![],
+            direction,
         }
     }
     pub fn parent(&self) -> FrameHandle {
         return self.parent_frame_handle;
     }
-    pub fn update(&mut self, frames: &mut FrameRenderer) {
-        let parent = frames.get(self.parent_frame_handle.index());
+    /// width and height of the laid out tracks, can be larger than the parent frame
+    pub fn content_size(&self) -> [VUnit; 2] {
+        self.content
+    }
+    /// bounds of every track cell from the last update, in the parent frame's child space
+    pub fn solved_cells(&self) -> &[BBox] {
+        &self.solved
+    }
+    /// switches to the widest breakpoint that fits and places every frame again if it changed,
+    /// returns the frames it had to hide
+    fn select_template(
+        &mut self,
+        grid: usize,
+        width: VUnit,
+        frames: &mut FrameStore,
+    ) -> Vec<LayoutError> {
+        let active = self
+            .templates
+            .iter()
+            .rposition(|(min_width, _)| *min_width <= width)
+            .unwrap_or(0);
+        if active == self.active {
+            return vec![];
+        }
+        self.active = active;
+        let [x_spacer, y_spacer] = self.templates[active].1.clone();
+        (self.major_spacer, self.cross_spacer) = match self.expand_dir {
+            Some(GridExpandDir::X) => (x_spacer, y_spacer),
+            _ => (y_spacer, x_spacer),
+        };
+        self.handles.clear();
+        let parent = frames.get(self.parent_frame_handle.index()).data;
+        let mut errors = vec![];
+        for (handle, x, y) in self.placements.clone() {
+            if let Err(e) = self.place(grid, handle, x, y) {
+                // hidden until a breakpoint with room for it is active again
+                warn!("frame {} was not placed: {e}", handle.index());
+                errors.push(LayoutError::Hidden {
+                    grid,
+                    frame: handle.index(),
+                });
+                let hidden = BBox {
+                    w: 0.into(),
+                    h: 0.into(),
+                    ..parent
+                };
+                frames.update(handle.index(), &hidden);
+            }
+        }
+        errors
+    }
+    /// grids without a direction follow the window's
+    pub(crate) fn is_rtl(&self, frames: &FrameStore) -> bool {
+        self.direction.unwrap_or(frames.direction()) == Direction::Rtl
+    }
+    /// `grid` is this grid's index, used for errors,
+    /// returns the frames a new breakpoint had to hide
+    pub fn update(&mut self, grid: usize, frames: &mut FrameStore) -> Vec<LayoutError> {
+        let width = frames.get(self.parent_frame_handle.index()).data.w;
+        let errors = self.select_template(grid, width, frames);
+        let ctx = frames.unit_context();
+        let rtl = self.is_rtl(frames);
+        let parent = frames.get(self.parent_frame_handle.index()).data;
         self.handles.sort_by_key(|h| (h.major, h.cross));
         let BBox {
             x: major_pos,
@@ -145,12 +231,12 @@ impl GridData {
             w: major_len,
             h: cross_len,
         } = match self.expand_dir {
-            Some(GridExpandDir::X) => parent.data,
+            Some(GridExpandDir::X) => parent,
             _ => BBox {
-                x: parent.data.y,
-                y: parent.data.x,
-                w: parent.data.h,
-                h: parent.data.w,
+                x: parent.y,
+                y: parent.x,
+                w: parent.h,
+                h: parent.w,
             },
         };
         let cross_solve: Vec<_> = solve_spacer(
@@ -159,8 +245,14 @@ impl GridData {
             |h| h.cross,
             cross_pos,
             cross_len,
+            ctx,
         )
         .collect();
+        let cross_end = cross_solve
+            .last()
+            .map_or(cross_pos, |s| s.pos + s.len);
+        let mut major_end = major_pos;
+        let mut solved = vec![];
 
         for cross_index in 0..self.cross_spacer.len() {
             //debug!("major_index: {}", major_index);
@@ -171,10 +263,12 @@ impl GridData {
                 |h| h.major,
                 major_pos,
                 major_len,
+                ctx,
             );
             major_solve.for_each(|solve| {
+                major_end = major_end.max(solve.pos + solve.len);
                 let cross_solve = &cross_solve[cross_index];
-                let bounds = match self.expand_dir {
+                let mut bounds = match self.expand_dir {
                     Some(GridExpandDir::X) => BBox {
                         x: solve.pos,
                         y: cross_solve.pos,
@@ -188,15 +282,28 @@ impl GridData {
                         w: cross_solve.len,
                     },
                 };
+                // tracks are mirrored around the parent, so logical column 0 is the rightmost
+                if rtl {
+                    bounds.x = parent.x + parent.x + parent.w - bounds.x - bounds.w;
+                }
+                solved.push(bounds);
                 //debug!("cross index: {}", cross_index);
                 major_iter
                     .by_ref()
                     .take(solve.count.min(1))
                     .for_each(|loc| {
+                        frames.update_rtl(loc.handle.index(), rtl);
                         frames.update(loc.handle.index(), &bounds);
                     })
             })
         }
+        self.solved = solved;
+        let (major, cross) = (major_end - major_pos, cross_end - cross_pos);
+        self.content = match self.expand_dir {
+            Some(GridExpandDir::X) => [major, cross],
+            _ => [cross, major],
+        };
+        errors
     }
 
     fn find_next_slot<'a, T>(
@@ -237,36 +344,118 @@ impl GridData {
         });
         self.find_next_slot(candidates, &self.cross_spacer.as_slice(), |h| h.cross)
     }
+    /// every placed frame with its column and row
+    pub fn cells(&self) -> impl Iterator<Item = (FrameHandle, [usize; 2])> + '_ {
+        self.handles.iter().map(|h| {
+            let cell = match self.expand_dir {
+                Some(GridExpandDir::X) => [h.major, h.cross],
+                _ => [h.cross, h.major],
+            };
+            (h.handle, cell)
+        })
+    }
+    /// number of columns and rows
+    pub fn track_counts(&self) -> [usize; 2] {
+        match self.expand_dir {
+            Some(GridExpandDir::X) => [self.major_spacer.len(), self.cross_spacer.len()],
+            _ => [self.cross_spacer.len(), self.major_spacer.len()],
+        }
+    }
+    /// the first cell without a frame, majors first, in the tracks that aren't `None`
+    fn free_cell(
+        &self,
+        major_index: Option<usize>,
+        cross_index: Option<usize>,
+    ) -> Option<(usize, usize)> {
+        let majors = major_index.map_or(0..self.major_spacer.len(), |m| m..m + 1);
+        majors.into_iter().find_map(|m| {
+            let mut crosses = cross_index.map_or(0..self.cross_spacer.len(), |c| c..c + 1);
+            crosses
+                .find(|&c| !self.handles.iter().any(|h| h.major == m && h.cross == c))
+                .map(|c| (m, c))
+        })
+    }
+    /// `x` and `y` are checked against the breakpoint with the most tracks,
+    /// frames whose cell is past the end of the one in use or already taken are placed
+    /// like frames without one, a direction with an auto-fill track takes any index
     pub fn add_frame(
         &mut self,
+        grid: usize,
         handle: FrameHandle,
         x: Option<XName>,
         y: Option<YName>,
-    ) -> Result<(), ()> {
-        let (major_index, cross_index) = match self.expand_dir {
-            Some(GridExpandDir::X) => (x.index(), y.index()),
-            _ => (y.index(), x.index()),
-        };
-        let next_major_index = match major_index {
-            None => {
-                if let Some(xi) = self.find_next_major_spacer(cross_index) {
-                    xi
-                } else {
-                    return Err(());
-                }
+    ) -> Result<(), LayoutError> {
+        let [x_len, y_len] = track_limits(&self.templates);
+        match (x.index(), y.index(), x_len, y_len) {
+            (Some(x), _, Some(len), _) if x >= len => {
+                return Err(LayoutError::XOutOfRange { grid, x, len })
+            }
+            (_, Some(y), _, Some(len)) if y >= len => {
+                return Err(LayoutError::YOutOfRange { grid, y, len })
             }
-            Some(n) => n,
+            _ => (),
+        }
+        self.place(grid, handle, x.index(), y.index())?;
+        self.placements.push((handle, x.index(), y.index()));
+        Ok(())
+    }
+    fn place(
+        &mut self,
+        grid: usize,
+        handle: FrameHandle,
+        x: Option<usize>,
+        y: Option<usize>,
+    ) -> Result<(), LayoutError> {
+        let [x_len, y_len] = self.track_counts();
+        if x_len == 0 || y_len == 0 {
+            return Err(LayoutError::EmptyGrid { grid });
+        }
+        let (x_spacer, y_spacer) = match self.expand_dir {
+            Some(GridExpandDir::X) => (&self.major_spacer, &self.cross_spacer),
+            _ => (&self.cross_spacer, &self.major_spacer),
+        };
+        let (explicit_x, explicit_y) = (x, y);
+        let x = x.and_then(|x| clamp_track(x_spacer, x));
+        let y = y.and_then(|y| clamp_track(y_spacer, y));
+        let moved = (explicit_x.is_some() && x.is_none()) || (explicit_y.is_some() && y.is_none());
+        let (mut major_index, mut cross_index) = match self.expand_dir {
+            Some(GridExpandDir::X) => (x, y),
+            _ => (y, x),
         };
-        //debug!("{next_major_index} ? {:?}", handle.index());
-        let next_cross_index = match cross_index {
+        // the auto-fill track holds any number of frames
+        let taken = |m: usize, c: usize| {
+            !matches!(self.major_spacer[m], SpacerUnit::Repeat(_))
+                && self.handles.iter().any(|h| h.major == m && h.cross == c)
+        };
+        let collides = match (major_index, cross_index) {
+            (Some(major), Some(cross)) => taken(major, cross),
+            _ => false,
+        };
+        if moved || collides {
+            (major_index, cross_index) = (None, None);
+        }
+        let cell = match (major_index, cross_index) {
+            (Some(major), Some(cross)) => Some((major, cross)),
+            _ => self.free_cell(major_index, cross_index),
+        };
+        // a full grid only takes more frames along its auto-fill track
+        let (next_major_index, next_cross_index) = match cell {
+            Some(cell) => cell,
+            None if self.expand_dir.is_none() => return Err(LayoutError::GridFull { grid }),
             None => {
-                if let Some(yi) = self.find_next_cross_spacer(major_index) {
-                    yi
-                } else {
-                    return Err(());
+                let repeat = self
+                    .major_spacer
+                    .iter()
+                    .rposition(|u| matches!(u, SpacerUnit::Repeat(_)));
+                let major = major_index
+                    .or(repeat)
+                    .or_else(|| self.find_next_major_spacer(cross_index));
+                let cross = cross_index.or_else(|| self.find_next_cross_spacer(major_index));
+                match (major, cross) {
+                    (Some(major), Some(cross)) => (major, cross),
+                    _ => return Err(LayoutError::EmptyGrid { grid }),
                 }
             }
-            Some(n) => n,
         };
         //debug!("{next_major_index} {next_cross_index} {:?}", handle.index());
         self.handles.push(HandleSpacerLocation {
@@ -274,6 +463,132 @@ impl GridData {
             cross: next_cross_index,
             handle,
         });
-        return Ok(());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+    use crate::{frame::FrameData, handle::Handle};
+
+    fn grid(x: GridSpacer, y: GridSpacer, expand_dir: Option<GridExpandDir>) -> GridData {
+        GridData::new(FrameHandle::new(0), vec![(0.into(), [x, y])], expand_dir, None)
+    }
+    fn units(n: usize) -> GridSpacer {
+        vec![SpacerUnit::Unit(10.into()); n]
+    }
+
+    #[test]
+    fn auto_frames_fill_free_cells_in_order() {
+        let mut data = grid(units(2), units(2), None);
+        for frame in 1..=4 {
+            data.add_frame(0, FrameHandle::new(frame), None, None).unwrap();
+        }
+        let cells: Vec<_> = data.cells().map(|(_, cell)| cell).collect();
+        assert_eq!(cells, [[0, 0], [1, 0], [0, 1], [1, 1]]);
+        assert!(matches!(
+            data.add_frame(0, FrameHandle::new(5), None, None),
+            Err(LayoutError::GridFull { grid: 0 })
+        ));
+    }
+
+    #[test]
+    fn auto_frames_skip_taken_cells() {
+        let mut data = grid(units(2), units(2), None);
+        data.add_frame(0, FrameHandle::new(1), Some(Handle::new(0)), Some(Handle::new(0)))
+            .unwrap();
+        data.add_frame(0, FrameHandle::new(2), None, Some(Handle::new(0))).unwrap();
+        data.add_frame(0, FrameHandle::new(3), None, None).unwrap();
+        let cells: Vec<_> = data.cells().map(|(_, cell)| cell).collect();
+        assert_eq!(cells, [[0, 0], [1, 0], [0, 1]]);
+    }
+
+    #[test]
+    fn auto_fill_takes_any_index() {
+        let mut x = units(1);
+        x.push(SpacerUnit::Repeat(10.into()));
+        let mut data = grid(x, units(1), Some(GridExpandDir::X));
+        data.add_frame(0, FrameHandle::new(1), Some(Handle::new(5)), None).unwrap();
+        data.add_frame(0, FrameHandle::new(2), None, None).unwrap();
+        data.add_frame(0, FrameHandle::new(3), None, None).unwrap();
+        assert!(matches!(
+            data.add_frame(0, FrameHandle::new(4), None, Some(Handle::new(1))),
+            Err(LayoutError::YOutOfRange { grid: 0, y: 1, len: 1 })
+        ));
+        let cells: Vec<_> = data.cells().map(|(_, cell)| cell).collect();
+        assert_eq!(cells, [[1, 0], [0, 0], [1, 0]]);
+    }
+
+    #[test]
+    fn breakpoint_without_room_reports_hidden_frames() {
+        let wide = vec![(0.into(), [units(2), units(1)]), (600.into(), [units(1), units(1)])];
+        let mut data = GridData::new(FrameHandle::new(0), wide, None, None);
+        let mut frames = FrameStore::default();
+        for _ in 0..3 {
+            frames.add(FrameData::zeroed());
+        }
+        data.add_frame(0, FrameHandle::new(1), None, None).unwrap();
+        data.add_frame(0, FrameHandle::new(2), None, None).unwrap();
+        let window = |w: i32| BBox {
+            x: 0.into(),
+            y: 0.into(),
+            w: w.into(),
+            h: 100.into(),
+        };
+        frames.update(0, &window(400));
+        assert!(data.update(0, &mut frames).is_empty());
+        frames.update(0, &window(800));
+        assert_eq!(data.update(0, &mut frames), [LayoutError::Hidden { grid: 0, frame: 2 }]);
+        assert_eq!(frames.get(2).data.w, 0.into());
+        frames.update(0, &window(400));
+        assert!(data.update(0, &mut frames).is_empty());
+    }
+
+    #[test]
+    fn narrower_breakpoint_moves_explicit_frames_to_free_cells() {
+        let templates = vec![(0.into(), [units(1), units(3)]), (600.into(), [units(3), units(2)])];
+        let mut data = GridData::new(FrameHandle::new(0), templates, None, None);
+        let mut frames = FrameStore::default();
+        for _ in 0..5 {
+            frames.add(FrameData::zeroed());
+        }
+        let window = |w: i32| BBox {
+            x: 0.into(),
+            y: 0.into(),
+            w: w.into(),
+            h: 100.into(),
+        };
+        frames.update(0, &window(800));
+        assert!(data.update(0, &mut frames).is_empty());
+        for x in 0..3 {
+            data.add_frame(0, FrameHandle::new(x + 1), Some(Handle::new(x)), Some(Handle::new(0)))
+                .unwrap();
+        }
+        assert!(data.update(0, &mut frames).is_empty());
+        let cells: Vec<_> = data.cells().map(|(_, cell)| cell).collect();
+        assert_eq!(cells, [[0, 0], [1, 0], [2, 0]]);
+        // one column, the frames past it and the one landing on a taken cell move down
+        frames.update(0, &window(400));
+        assert!(data.update(0, &mut frames).is_empty());
+        let mut cells: Vec<_> = data.cells().collect();
+        cells.sort_by_key(|(h, _)| h.index());
+        let cells: Vec<_> = cells.into_iter().map(|(_, cell)| cell).collect();
+        assert_eq!(cells, [[0, 0], [0, 1], [0, 2]]);
+        let bounds: Vec<_> = (1..4).map(|i| frames.get(i).data.y).collect();
+        assert_eq!(bounds, [0.into(), 10.into(), 20.into()]);
+        // a fourth frame fits the wide tracks, in one column it has no cell left and is hidden
+        assert!(matches!(
+            data.add_frame(0, FrameHandle::new(4), Some(Handle::new(1)), None),
+            Err(LayoutError::GridFull { grid: 0 })
+        ));
+        frames.update(0, &window(800));
+        assert!(data.update(0, &mut frames).is_empty());
+        data.add_frame(0, FrameHandle::new(4), Some(Handle::new(1)), Some(Handle::new(1)))
+            .unwrap();
+        frames.update(0, &window(400));
+        assert_eq!(data.update(0, &mut frames), [LayoutError::Hidden { grid: 0, frame: 4 }]);
     }
 }
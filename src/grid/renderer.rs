@@ -1,8 +1,10 @@
 use log::error;
 
-use crate::frame::{FrameHandle, FrameRenderer};
+use crate::error::LayoutError;
+use crate::frame::{FrameHandle, FrameStore};
 use crate::handle::HandleLike;
 use crate::manager::BBox;
+use crate::units::VUnit;
 
 use crate::handle::Handle;
 
@@ -10,6 +12,7 @@ use super::{XName, YName};
 use crate::grid::data::GridData;
 use crate::handle::FallableHandleLike;
 
+#[derive(Default)]
 pub struct GridRenderer {
     data: Vec<GridData>,
 }
@@ -20,13 +23,16 @@ pub struct GridT {}
 pub type GridHandle = Handle<GridT>;
 
 impl GridRenderer {
-    pub fn new(_device: &wgpu::Device, _config: &wgpu::SurfaceConfiguration) -> Self {
+    pub fn new() -> Self {
         Self { data: vec![] }
     }
-    pub fn prepare(&mut self, frame_renderer: &mut FrameRenderer, _queue: &wgpu::Queue) {
-        for g in &mut self.data {
-            g.update(frame_renderer);
-        }
+    /// returns the frames the active breakpoints couldn't place
+    pub fn prepare(&mut self, frame_renderer: &mut FrameStore) -> Vec<LayoutError> {
+        self.data
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, g)| g.update(i, frame_renderer))
+            .collect()
     }
     // pub fn render<'rp>(&'rp self, _render_pass: &mut wgpu::RenderPass<'rp>) {
     //     ()
@@ -35,33 +41,47 @@ impl GridRenderer {
         &mut self,
         grid_handle: GridHandle,
         _bounds: &BBox,
-        frame_renderer: &mut FrameRenderer,
+        frame_renderer: &mut FrameStore,
     ) {
-        self.data[grid_handle.index()].update(frame_renderer);
+        self.data[grid_handle.index()].update(grid_handle.index(), frame_renderer);
     }
     pub fn add_frame(
         &mut self,
-        _frame_renderer: &mut FrameRenderer,
+        _frame_renderer: &mut FrameStore,
         grid_handle: GridHandle,
         frame_handle: FrameHandle,
         x: Option<XName>,
         y: Option<YName>,
-    ) {
-        match self.data[grid_handle.index()].add_frame(frame_handle, x, y) {
-            Ok(()) => (),
-            Err(()) => error!(
-                "couldn't add {} to grid at x:{:?} y:{:?}",
+    ) -> Result<(), LayoutError> {
+        let grid = grid_handle.index();
+        let res = match self.data.get_mut(grid) {
+            Some(g) => g.add_frame(grid, frame_handle, x, y),
+            None => Err(LayoutError::UnknownGrid { grid }),
+        };
+        if let Err(e) = res {
+            error!(
+                "couldn't add {} to grid at x:{:?} y:{:?}: {e}",
                 frame_handle.index(),
                 x.index(),
                 y.index()
-            ),
+            );
         }
         //self.data[grid_handle.index()].update(frame_renderer);
+        res
     }
     pub fn add(&mut self, g: GridData) -> GridHandle {
         self.data.push(g);
         return GridHandle::new(self.data.len() - 1);
     }
+    pub fn content_size(&self, grid: GridHandle) -> [VUnit; 2] {
+        self.data[grid.index()].content_size()
+    }
+    pub fn get(&self, grid: GridHandle) -> &GridData {
+        &self.data[grid.index()]
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
     pub fn get_parent_handle(&self, grid: GridHandle) -> FrameHandle {
         self.data[grid.index()].parent()
     }
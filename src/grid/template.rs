@@ -0,0 +1,303 @@
+use crate::{
+    error::{TemplateError, TemplateErrorKind},
+    units::UserUnits,
+};
+
+use super::{GridSpacer, SpacerUnit};
+
+const AUTO_FILL: &str = "auto-fill";
+const REPEAT: &str = "repeat(";
+
+/// parses track lists like `"200px 1fr 2fr 25%"`, `"repeat(16, 1fr)"` and `"auto-fill"`,
+/// `vw`, `vh` and `rem` lengths are accepted as well,
+/// `auto-fill` on its own is short for `repeat(auto-fill, 1fr)`
+pub(crate) fn parse(template: &str) -> Result<GridSpacer, TemplateError> {
+    let mut parser = Parser { src: template, pos: 0 };
+    let mut res = GridSpacer::new();
+    parser.skip_ws();
+    if parser.at_end() {
+        return Err(parser.error(TemplateErrorKind::Empty));
+    }
+    while !parser.at_end() {
+        let start = parser.pos;
+        if parser.eat(REPEAT) {
+            parser.repeat(&mut res)?;
+        } else if parser.eat(AUTO_FILL) {
+            res.push(SpacerUnit::Repeat(UserUnits::Fraction(1)));
+        } else {
+            res.push(SpacerUnit::Unit(parser.unit()?));
+        }
+        if res.iter().filter(|s| matches!(s, SpacerUnit::Repeat(_))).count() > 1 {
+            return Err(TemplateError {
+                pos: start,
+                kind: TemplateErrorKind::MultipleAutoFill,
+            });
+        }
+        parser.expect_separator()?;
+    }
+    Ok(res)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+    fn at_end(&self) -> bool {
+        self.rest().is_empty()
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn error(&self, kind: TemplateErrorKind) -> TemplateError {
+        TemplateError {
+            pos: self.pos,
+            kind,
+        }
+    }
+    fn unexpected(&self) -> TemplateError {
+        match self.peek() {
+            Some(c) => self.error(TemplateErrorKind::UnexpectedChar(c)),
+            None => self.error(TemplateErrorKind::UnexpectedEnd),
+        }
+    }
+    fn skip_ws(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn eat(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+    /// tracks are separated by whitespace, a `,` or `)` is left for `repeat`
+    fn expect_separator(&mut self) -> Result<(), TemplateError> {
+        match self.peek() {
+            None | Some(',') | Some(')') => Ok(()),
+            Some(c) if c.is_whitespace() => {
+                self.skip_ws();
+                Ok(())
+            }
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+    fn unit(&mut self) -> Result<UserUnits, TemplateError> {
+        let start = self.pos;
+        let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+        if number.is_empty() {
+            return Err(self.unexpected());
+        }
+        let suffix_pos = self.pos;
+        let suffix = self.take_while(|c| c.is_ascii_alphabetic() || c == '%');
+        let invalid = TemplateError {
+            pos: start,
+            kind: TemplateErrorKind::InvalidNumber,
+        };
+        match suffix {
+            "px" => number.parse().map(UserUnits::Pixel).map_err(|_| invalid),
+            "fr" => match number.parse() {
+                Ok(0) | Err(_) => Err(invalid),
+                Ok(f) => Ok(UserUnits::Fraction(f)),
+            },
+            "%" => number
+                .parse::<f32>()
+                .map(|p| UserUnits::Ratio(p / 100.0))
+                .map_err(|_| invalid),
+            "vw" | "vh" | "rem" => {
+                let f = number.parse::<f32>().map_err(|_| invalid)?;
+                Ok(match suffix {
+                    "vw" => UserUnits::Vw(f),
+                    "vh" => UserUnits::Vh(f),
+                    _ => UserUnits::Rem(f),
+                })
+            }
+            "" if number == "0" => Ok(UserUnits::Zero),
+            _ => Err(TemplateError {
+                pos: suffix_pos,
+                kind: TemplateErrorKind::UnknownUnit,
+            }),
+        }
+    }
+    /// everything after `repeat(`
+    fn repeat(&mut self, res: &mut GridSpacer) -> Result<(), TemplateError> {
+        self.skip_ws();
+        let count_pos = self.pos;
+        let count = match self.eat(AUTO_FILL) {
+            true => None,
+            false => {
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                if digits.is_empty() {
+                    return Err(self.unexpected());
+                }
+                match digits.parse::<usize>() {
+                    Ok(0) => {
+                        return Err(TemplateError {
+                            pos: count_pos,
+                            kind: TemplateErrorKind::ZeroRepeat,
+                        })
+                    }
+                    Ok(n) => Some(n),
+                    Err(_) => {
+                        return Err(TemplateError {
+                            pos: count_pos,
+                            kind: TemplateErrorKind::InvalidNumber,
+                        })
+                    }
+                }
+            }
+        };
+        self.skip_ws();
+        if !self.eat(",") {
+            return Err(self.unexpected());
+        }
+        self.skip_ws();
+        let mut units = vec![];
+        let mut second_pos = None;
+        while !matches!(self.peek(), Some(')') | None) {
+            if units.len() == 1 {
+                second_pos = Some(self.pos);
+            }
+            units.push(self.unit()?);
+            self.expect_separator()?;
+            if self.peek() == Some(',') {
+                return Err(self.unexpected());
+            }
+        }
+        if units.is_empty() {
+            return Err(self.unexpected());
+        }
+        if !self.eat(")") {
+            return Err(self.unexpected());
+        }
+        match (count, units.as_slice()) {
+            (Some(n), _) => {
+                for _ in 0..n {
+                    res.extend(units.iter().map(|u| SpacerUnit::Unit(u.clone())));
+                }
+            }
+            (None, [u]) => res.push(SpacerUnit::Repeat(u.clone())),
+            // an expanding track repeats a single unit
+            (None, _) => {
+                return Err(TemplateError {
+                    pos: second_pos.unwrap_or(count_pos),
+                    kind: TemplateErrorKind::AutoFillTracks,
+                })
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        frame::FrameHandle,
+        grid::GridBuilder,
+        handle::HandleLike,
+        units::UserUnits::*,
+    };
+
+    fn err(template: &str) -> (usize, TemplateErrorKind) {
+        let e = parse(template).unwrap_err();
+        (e.pos, e.kind)
+    }
+
+    #[test]
+    fn units() {
+        let units = parse("200px 1fr 25% 0 1.5rem 10vw 20vh").unwrap();
+        let expected = [
+            Pixel(200),
+            Fraction(1),
+            Ratio(0.25),
+            Zero,
+            Rem(1.5),
+            Vw(10.0),
+            Vh(20.0),
+        ];
+        assert_eq!(units, expected.map(SpacerUnit::Unit));
+    }
+
+    #[test]
+    fn repeat() {
+        let units = parse(" 10px repeat( 2 , 1fr 2fr ) 5px ").unwrap();
+        let expected = [Pixel(10), Fraction(1), Fraction(2), Fraction(1), Fraction(2), Pixel(5)];
+        assert_eq!(units, expected.map(SpacerUnit::Unit));
+    }
+
+    #[test]
+    fn auto_fill() {
+        assert_eq!(parse("auto-fill").unwrap(), [SpacerUnit::Repeat(Fraction(1))]);
+        assert_eq!(
+            parse("100px repeat(auto-fill, 50px)").unwrap(),
+            [SpacerUnit::Unit(Pixel(100)), SpacerUnit::Repeat(Pixel(50))]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(err("   "), (3, TemplateErrorKind::Empty));
+        assert_eq!(err("1fr ?"), (4, TemplateErrorKind::UnexpectedChar('?')));
+        assert_eq!(err("1fr,2fr"), (3, TemplateErrorKind::UnexpectedChar(',')));
+        assert_eq!(err("repeat(2, 1fr"), (13, TemplateErrorKind::UnexpectedEnd));
+        assert_eq!(err("repeat(2,"), (9, TemplateErrorKind::UnexpectedEnd));
+        assert_eq!(err("1px 0fr"), (4, TemplateErrorKind::InvalidNumber));
+        assert_eq!(err("1.5px"), (0, TemplateErrorKind::InvalidNumber));
+        assert_eq!(err("1..2%"), (0, TemplateErrorKind::InvalidNumber));
+        assert_eq!(err("10px 5em"), (6, TemplateErrorKind::UnknownUnit));
+        assert_eq!(err("12"), (2, TemplateErrorKind::UnknownUnit));
+        assert_eq!(err("repeat(0, 1fr)"), (7, TemplateErrorKind::ZeroRepeat));
+        assert_eq!(err("auto-fill 1fr auto-fill"), (14, TemplateErrorKind::MultipleAutoFill));
+        assert_eq!(
+            err("repeat(auto-fill, 1fr 2fr)"),
+            (22, TemplateErrorKind::AutoFillTracks)
+        );
+    }
+
+    #[test]
+    fn auto_fill_template_expands_the_grid() {
+        let mut g = GridBuilder::new(FrameHandle::new(0));
+        g.widths().template("1fr auto-fill").unwrap().build();
+        g.heights().template("1fr").unwrap().build();
+        let mut data = g.build();
+        for frame in 1..=4 {
+            data.add_frame(0, FrameHandle::new(frame), None, None).unwrap();
+        }
+        let cells: Vec<_> = data.cells().map(|(_, cell)| cell).collect();
+        assert_eq!(cells, [[0, 0], [1, 0], [1, 0], [1, 0]]);
+    }
+
+    #[test]
+    fn auto_fill_in_both_directions_is_an_error() {
+        let mut g = GridBuilder::new(FrameHandle::new(0));
+        g.widths().template("auto-fill").unwrap().build();
+        let err = g.heights().template("1fr auto-fill").err().unwrap();
+        assert_eq!(err.kind, TemplateErrorKind::ExpandsBothDirections);
+        assert_eq!(err.pos, 4);
+        // breakpoints can't expand the other way either
+        let err = g.breakpoint(100).heights().template("auto-fill").err().unwrap();
+        assert_eq!(err.kind, TemplateErrorKind::ExpandsBothDirections);
+        // the same direction is fine
+        g.breakpoint(200).widths().template("10px auto-fill").unwrap().build();
+    }
+
+    #[test]
+    #[should_panic(expected = "one expanding direction")]
+    fn expanding_both_directions_panics_on_build() {
+        let mut g = GridBuilder::new(FrameHandle::new(0));
+        g.widths().template("auto-fill").unwrap().build();
+        g.heights().add_expanding(Pixel(10)).build();
+    }
+}
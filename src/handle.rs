@@ -24,6 +24,15 @@ pub trait HandleLike {
     fn index(&self) -> usize;
 }
 
+// a derive would require `T: PartialEq`, handles only compare their index
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
 impl<T> HandleLike for Handle<T> {
     fn new(i: usize) -> Self {
         Self {
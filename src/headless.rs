@@ -0,0 +1,320 @@
+use std::{fs::File, io::BufWriter, io::Write, path::Path, sync::mpsc};
+
+use wgpu::util::DeviceExt;
+
+use crate::{
+    component::State,
+    error::HeadlessError,
+    frame::FrameRenderer,
+    images::ImageRenderer,
+    layout::Layout,
+    manager::{supported_samples, VERTICES},
+    text::TextRenderer,
+};
+
+/// draws the frame pipeline into an offscreen texture instead of a window's surface,
+/// one logical pixel is one pixel of the image
+pub struct HeadlessRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    frame_renderer: FrameRenderer,
+    text_renderer: TextRenderer,
+    image_renderer: ImageRenderer,
+    vertex_buffer: wgpu::Buffer,
+    target: wgpu::Texture,
+    readback: wgpu::Buffer,
+    width: u32,
+    height: u32,
+}
+
+impl HeadlessRenderer {
+    /// srgb, so the readback holds encoded colors like a png expects
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// falls back to a software adapter when there is no gpu
+    pub async fn new(width: u32, height: u32) -> Result<Self, HeadlessError> {
+        Self::new_multisampled(width, height, 1).await
+    }
+    /// like `new` with `sample_count` samples per pixel, it falls back to the highest one supported
+    pub async fn new_multisampled(
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> Result<Self, HeadlessError> {
+        let invalid_size = HeadlessError::InvalidSize { width, height };
+        if width == 0 || height == 0 {
+            return Err(invalid_size);
+        }
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let mut options = wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        };
+        let adapter = match instance.request_adapter(&options).await {
+            Some(adapter) => adapter,
+            None => {
+                options.force_fallback_adapter = true;
+                instance
+                    .request_adapter(&options)
+                    .await
+                    .ok_or(HeadlessError::NoAdapter)?
+            }
+        };
+        let max = adapter.limits().max_texture_dimension_2d;
+        if width > max || height > max {
+            return Err(invalid_size);
+        }
+        let (sample_count, required_features) =
+            supported_samples(&adapter, Self::FORMAT, sample_count);
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("headless device"),
+                    required_features,
+                    required_limits: adapter.limits(),
+                },
+                None,
+            )
+            .await
+            .map_err(HeadlessError::RequestDevice)?;
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("headless render target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("headless readback buffer"),
+            size: (Self::padded_row(width) * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(VERTICES),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        Ok(Self {
+            frame_renderer: FrameRenderer::new(&device, Self::FORMAT, sample_count),
+            text_renderer: TextRenderer::new(&device, Self::FORMAT, sample_count),
+            image_renderer: ImageRenderer::new(&device, Self::FORMAT, sample_count),
+            device,
+            queue,
+            vertex_buffer,
+            target,
+            readback,
+            width,
+            height,
+        })
+    }
+    /// rows of the readback buffer are padded to wgpu's copy alignment
+    fn padded_row(width: u32) -> u32 {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        (width * 4).div_ceil(align) * align
+    }
+    /// tightly packed rgba rows, top row first
+    pub fn render(&mut self, layout: &mut Layout) -> Result<Vec<u8>, HeadlessError> {
+        self.frame_renderer
+            .prepare(&self.device, &mut layout.frames, &self.queue);
+        self.image_renderer
+            .prepare(&mut layout.images, &layout.frames, &self.device, &self.queue);
+        self.text_renderer
+            .prepare(&layout.text, &layout.frames, &self.device, &self.queue);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("headless encoder"),
+            });
+        self.frame_renderer.render(
+            &self.device,
+            &mut encoder,
+            &self.target,
+            &self.vertex_buffer,
+            &[&self.image_renderer, &self.text_renderer],
+            wgpu::Color::BLACK,
+        );
+        let padded_row = Self::padded_row(self.width);
+        encoder.copy_texture_to_buffer(
+            self.target.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            self.target.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        let (send, recv) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |res| {
+            let _ = send.send(res);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        recv.recv()
+            .expect("map callback runs during poll")
+            .map_err(HeadlessError::BufferMap)?;
+        let row = (self.width * 4) as usize;
+        let res = slice
+            .get_mapped_range()
+            .chunks(padded_row as usize)
+            .flat_map(|r| &r[..row])
+            .copied()
+            .collect();
+        self.readback.unmap();
+        Ok(res)
+    }
+}
+
+/// writes tightly packed rgba rows as an 8 bit png
+pub fn write_png(
+    w: impl Write,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> Result<(), HeadlessError> {
+    let mut encoder = png::Encoder::new(w, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba)?;
+    Ok(())
+}
+
+/// builds `App` in a `width` by `height` window and renders it without a window or surface
+pub fn render_rgba<App: State<Param = ()>>(
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, HeadlessError> {
+    let mut layout = Layout::solved::<App>(width, height);
+    let mut renderer = pollster::block_on(HeadlessRenderer::new(width, height))?;
+    renderer.render(&mut layout)
+}
+
+pub fn render_png<App: State<Param = ()>>(
+    width: u32,
+    height: u32,
+    path: impl AsRef<Path>,
+) -> Result<(), HeadlessError> {
+    let rgba = render_rgba::<App>(width, height)?;
+    write_png(BufWriter::new(File::create(path)?), width, height, &rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        component::{Builder, UpdateQueue},
+        frame::FrameHandle,
+        handle::HandleLike,
+        images::{ImageHandle, ImageMessage},
+        manager::Corners,
+        render_actor::{FrameMessage, UpdateMessage},
+        units::UserUnits,
+    };
+
+    struct Halves;
+    impl State for Halves {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.widths().template("1fr 1fr").unwrap().build();
+            g.heights().template("1fr").unwrap().build();
+            let g = builder.grid(g);
+            builder.frame::<()>((), g, None, None);
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn zero_size_is_an_error() {
+        for (width, height) in [(0, 8), (8, 0)] {
+            let res = pollster::block_on(HeadlessRenderer::new(width, height));
+            assert!(matches!(res, Err(HeadlessError::InvalidSize { .. })));
+        }
+    }
+
+    #[test]
+    fn renders_frames() {
+        let (width, height) = (16, 8);
+        let mut layout = Layout::solved::<Halves>(width, height);
+        let red = FrameMessage {
+            color: Some(Color::rgb(255, 0, 0)),
+            ..FrameMessage::default()
+        };
+        layout.apply(UpdateMessage::ModifyFrame(FrameHandle::new(1), red)).unwrap();
+        layout.solve();
+        let mut renderer = pollster::block_on(HeadlessRenderer::new(width, height)).unwrap();
+        let rgba = renderer.render(&mut layout).unwrap();
+        assert_eq!(rgba.len(), (width * height * 4) as usize);
+        let pixel = |x: u32, y: u32| &rgba[((y * width + x) * 4) as usize..][..4];
+        assert_eq!(pixel(2, 4), [255, 0, 0, 255]);
+        assert_eq!(pixel(13, 4), [255, 255, 255, 255]);
+    }
+
+    fn show(layout: &mut Layout, image: ImageHandle, rgba: [u8; 4], size: [u32; 2]) {
+        let [w, h] = size;
+        let pixels = image::RgbaImage::from_pixel(w, h, image::Rgba(rgba));
+        layout.apply(UpdateMessage::NewImage(image, pixels)).unwrap();
+        let msg = ImageMessage {
+            image: Some(Some(image)),
+            fit: None,
+        };
+        layout.apply(UpdateMessage::ModifyImage(FrameHandle::new(1), msg)).unwrap();
+        layout.solve();
+    }
+
+    #[test]
+    fn images_clip_to_rounded_corners() {
+        let (width, height) = (16, 8);
+        let mut layout = Layout::solved::<Halves>(width, height);
+        let round = FrameMessage {
+            radius: Some(Corners {
+                top_left: UserUnits::Pixel(4),
+                top_right: UserUnits::Pixel(4),
+                bottom_right: UserUnits::Pixel(4),
+                bottom_left: UserUnits::Pixel(4),
+            }),
+            ..FrameMessage::default()
+        };
+        layout.apply(UpdateMessage::ModifyFrame(FrameHandle::new(1), round)).unwrap();
+        show(&mut layout, ImageHandle::new(0), [0, 0, 255, 255], [4, 4]);
+        let mut renderer = pollster::block_on(HeadlessRenderer::new(width, height)).unwrap();
+        let rgba = renderer.render(&mut layout).unwrap();
+        let pixel = |x: u32, y: u32| &rgba[((y * width + x) * 4) as usize..][..4];
+        assert_eq!(pixel(0, 0), [255, 255, 255, 255]);
+        assert_eq!(pixel(4, 4), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn oversized_and_replaced_images_are_drawn() {
+        let (width, height) = (16, 8);
+        let mut layout = Layout::solved::<Halves>(width, height);
+        // wider than an atlas page, so it gets a texture of its own
+        show(&mut layout, ImageHandle::new(0), [0, 255, 0, 255], [2100, 4]);
+        let mut renderer = pollster::block_on(HeadlessRenderer::new(width, height)).unwrap();
+        let rgba = renderer.render(&mut layout).unwrap();
+        assert_eq!(&rgba[((4 * width + 4) * 4) as usize..][..4], [0, 255, 0, 255]);
+        layout.apply(UpdateMessage::RemoveImage(ImageHandle::new(0))).unwrap();
+        show(&mut layout, ImageHandle::new(1), [0, 0, 255, 255], [4, 4]);
+        let rgba = renderer.render(&mut layout).unwrap();
+        assert_eq!(&rgba[((4 * width + 4) * 4) as usize..][..4], [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,6 @@
+mod atlas;
+mod renderer;
+mod store;
+
+pub use renderer::ImageRenderer;
+pub use store::{ImageFit, ImageHandle, ImageMessage, ImageStore};
@@ -0,0 +1,393 @@
+use image::RgbaImage;
+
+/// the image is larger than the biggest texture the device supports
+#[derive(Debug)]
+pub struct TooLarge;
+
+/// a texture images are packed into rows of, or that holds a single oversized image
+struct Page {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    size: [u32; 2],
+    /// x and y of the next image and the height of the current row
+    cursor: [u32; 3],
+    /// images on the page
+    live: usize,
+    /// set once an image was removed, its space is reused after a repack
+    dead: bool,
+    /// holds a single image larger than a shared page
+    own: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Slot {
+    page: usize,
+    origin: [u32; 2],
+    size: [u32; 2],
+}
+
+/// every loaded image packed into pages of rgba textures, drawn with the page's bind group
+pub struct ImageAtlas {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    page_size: u32,
+    max_size: u32,
+    /// `None` for pages that were freed, their index is reused
+    pages: Vec<Option<Page>>,
+    /// where every image is, `None` for removed images and ones that didn't fit
+    slots: Vec<Option<Slot>>,
+}
+
+impl ImageAtlas {
+    /// space left around every image so filtering doesn't bleed into its neighbours
+    const PADDING: u32 = 1;
+
+    pub fn new(device: &wgpu::Device, page_size: u32) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("image atlas layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("image atlas sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let max_size = device.limits().max_texture_dimension_2d;
+        Self {
+            layout,
+            sampler,
+            page_size: page_size.min(max_size),
+            max_size,
+            pages: vec![],
+            slots: vec![],
+        }
+    }
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+    /// the page of the image at `index` and its normalized xywh on it
+    pub fn uv(&self, index: usize) -> Option<(usize, [f32; 4])> {
+        let slot = self.slots.get(index).copied().flatten()?;
+        let [pw, ph] = self.pages[slot.page].as_ref()?.size.map(|s| s as f32);
+        let [x, y] = slot.origin;
+        let [w, h] = slot.size;
+        // texel centers on the edges, so filtering never reaches the padding
+        Some((
+            slot.page,
+            [
+                (x as f32 + 0.5) / pw,
+                (y as f32 + 0.5) / ph,
+                w.saturating_sub(1) as f32 / pw,
+                h.saturating_sub(1) as f32 / ph,
+            ],
+        ))
+    }
+    pub fn bind_group(&self, page: usize) -> Option<&wgpu::BindGroup> {
+        Some(&self.pages.get(page)?.as_ref()?.bind_group)
+    }
+    /// uploads the image at `index`, a full atlas repacks pages with removed images
+    /// or adds a page, images larger than a page get their own
+    pub fn add(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        index: usize,
+        image: &RgbaImage,
+    ) -> Result<(), TooLarge> {
+        if self.slots.len() <= index {
+            self.slots.resize(index + 1, None);
+        }
+        let (w, h) = image.dimensions();
+        if w > self.max_size || h > self.max_size {
+            return Err(TooLarge);
+        }
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        let slot = match w + Self::PADDING > self.page_size || h + Self::PADDING > self.page_size {
+            true => {
+                let page = self.add_page(device, [w, h], true);
+                Slot {
+                    page,
+                    origin: [0, 0],
+                    size: [w, h],
+                }
+            }
+            false => self.allocate(device, queue, [w, h]),
+        };
+        let page = self.pages[slot.page].as_mut().expect("slots point at live pages");
+        page.live += 1;
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &page.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: slot.origin[0],
+                    y: slot.origin[1],
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.as_raw(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(w * 4),
+                rows_per_image: Some(h),
+            },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.slots[index] = Some(slot);
+        Ok(())
+    }
+    /// frees the space of the image at `index`, empty pages are reset or dropped
+    pub fn remove(&mut self, index: usize) {
+        let Some(slot) = self.slots.get_mut(index).and_then(Option::take) else {
+            return;
+        };
+        let Some(page) = self.pages[slot.page].as_mut() else {
+            return;
+        };
+        page.live -= 1;
+        page.dead = true;
+        if page.live > 0 {
+            return;
+        }
+        // the first page stays around for the next image
+        match page.own || slot.page > 0 {
+            true => self.pages[slot.page] = None,
+            false => {
+                page.cursor = [0; 3];
+                page.dead = false;
+            }
+        }
+    }
+    /// a place for `size` on a shared page
+    fn allocate(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, size: [u32; 2]) -> Slot {
+        if let Some(slot) = self.find_space(size) {
+            return slot;
+        }
+        let dead: Vec<usize> = self
+            .pages
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.as_ref().is_some_and(|p| p.dead && !p.own))
+            .map(|(i, _)| i)
+            .collect();
+        if !dead.is_empty() {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("image atlas repack"),
+            });
+            for page in dead {
+                self.repack(device, &mut encoder, page);
+            }
+            queue.submit(Some(encoder.finish()));
+            if let Some(slot) = self.find_space(size) {
+                return slot;
+            }
+        }
+        let page = self.add_page(device, [self.page_size; 2], false);
+        self.find_space(size).unwrap_or(Slot {
+            page,
+            origin: [0, 0],
+            size,
+        })
+    }
+    fn find_space(&mut self, [w, h]: [u32; 2]) -> Option<Slot> {
+        self.pages.iter_mut().enumerate().find_map(|(i, page)| {
+            let page = page.as_mut().filter(|p| !p.own)?;
+            let origin = Self::advance(&mut page.cursor, page.size, [w, h])?;
+            Some(Slot {
+                page: i,
+                origin,
+                size: [w, h],
+            })
+        })
+    }
+    /// the next spot in the rows of a page, `cursor` moves past it
+    fn advance(cursor: &mut [u32; 3], [pw, ph]: [u32; 2], [w, h]: [u32; 2]) -> Option<[u32; 2]> {
+        let [mut x, mut y, mut row] = *cursor;
+        if x + w + Self::PADDING > pw {
+            x = 0;
+            y += row;
+            row = 0;
+        }
+        if x + w + Self::PADDING > pw || y + h + Self::PADDING > ph {
+            return None;
+        }
+        *cursor = [x + w + Self::PADDING, y, row.max(h + Self::PADDING)];
+        Some([x, y])
+    }
+    /// copies the images left on `page` to the start of a new texture in the order
+    /// they were placed, so they fit again
+    fn repack(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, page: usize) {
+        let size = self.pages[page].as_ref().expect("only live pages are repacked").size;
+        let (texture, bind_group) = self.create_texture(device, size);
+        let mut slots: Vec<&mut Slot> = self
+            .slots
+            .iter_mut()
+            .flatten()
+            .filter(|s| s.page == page)
+            .collect();
+        slots.sort_by_key(|s| (s.origin[1], s.origin[0]));
+        let old = self.pages[page].as_mut().expect("only live pages are repacked");
+        let mut cursor = [0; 3];
+        for slot in slots {
+            let origin = Self::advance(&mut cursor, size, slot.size)
+                .expect("images keep fitting when the ones before them are removed");
+            let [width, height] = slot.size;
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &old.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: slot.origin[0],
+                        y: slot.origin[1],
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: origin[0],
+                        y: origin[1],
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+            slot.origin = origin;
+        }
+        // the old texture is kept alive by the encoder until the copies ran
+        old.texture = texture;
+        old.bind_group = bind_group;
+        old.cursor = cursor;
+        old.dead = false;
+    }
+    fn add_page(&mut self, device: &wgpu::Device, size: [u32; 2], own: bool) -> usize {
+        let (texture, bind_group) = self.create_texture(device, size);
+        let page = Some(Page {
+            texture,
+            bind_group,
+            size,
+            cursor: [0; 3],
+            live: 0,
+            dead: false,
+            own,
+        });
+        match self.pages.iter().position(Option::is_none) {
+            Some(i) => {
+                self.pages[i] = page;
+                i
+            }
+            None => {
+                self.pages.push(page);
+                self.pages.len() - 1
+            }
+        }
+    }
+    fn create_texture(
+        &self,
+        device: &wgpu::Device,
+        [width, height]: [u32; 2],
+    ) -> (wgpu::Texture, wgpu::BindGroup) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("image atlas"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            // images are srgb like frame colors, sampling converts them to linear space
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("image atlas bg"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+        (texture, bind_group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: [u32; 2] = [10, 10];
+
+    #[test]
+    fn images_fill_rows_with_padding() {
+        let mut cursor = [0; 3];
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [4, 3]), Some([0, 0]));
+        assert_eq!(cursor, [5, 0, 4]);
+        // the padding on the right just reaches the page edge
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [4, 2]), Some([5, 0]));
+        assert_eq!(cursor, [10, 0, 4]);
+    }
+
+    #[test]
+    fn a_full_row_wraps_below_its_tallest_image() {
+        let mut cursor = [5, 0, 4];
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [6, 2]), Some([0, 4]));
+        assert_eq!(cursor, [7, 4, 3]);
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [2, 5]), Some([7, 4]));
+        assert_eq!(cursor, [10, 4, 6]);
+    }
+
+    #[test]
+    fn a_full_page_has_no_space() {
+        let mut cursor = [3, 4, 3];
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [3, 6]), None);
+        // wrapping doesn't help either, the cursor stays where it was
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [7, 5]), None);
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [10, 1]), None);
+        assert_eq!(cursor, [3, 4, 3]);
+        assert_eq!(ImageAtlas::advance(&mut cursor, PAGE, [6, 5]), Some([3, 4]));
+    }
+}
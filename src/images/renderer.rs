@@ -0,0 +1,339 @@
+use std::{mem, ops::Range};
+
+use bytemuck::{Pod, Zeroable};
+use log::warn;
+use wgpu::{include_wgsl, Device, MultisampleState, RenderPass, RenderPipeline};
+
+use crate::{
+    frame::{grown, FrameContent, FrameStore},
+    handle::HandleLike,
+    manager::{BBox, Vertex},
+};
+
+use super::{
+    atlas::{ImageAtlas, TooLarge},
+    store::{ImageFit, ImageStore},
+};
+
+#[derive(Pod, Zeroable, Clone, Copy, Debug)]
+#[repr(C)]
+struct ImageData {
+    /// logical pixels on screen
+    rect: [f32; 4],
+    uv: [f32; 4],
+    clip: [f32; 4],
+    /// screen space visible bounds of the frame, the image is clipped to its rounded corners
+    frame: [f32; 4],
+    /// logical pixels, [top left, top right, bottom right, bottom left]
+    radius: [f32; 4],
+    opacity: f32,
+}
+
+impl ImageData {
+    const fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as u64,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 1,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 16,
+                    shader_location: 2,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 32,
+                    shader_location: 3,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 48,
+                    shader_location: 5,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 64,
+                    shader_location: 6,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32,
+                    offset: 80,
+                    shader_location: 4,
+                },
+            ],
+        }
+    }
+}
+
+/// draws the image of every frame from the pages of an image atlas, one quad per frame
+pub struct ImageRenderer {
+    pipeline: RenderPipeline,
+    viewport_buffer: wgpu::Buffer,
+    viewport_bg: wgpu::BindGroup,
+    atlas: ImageAtlas,
+    image_buffer: wgpu::Buffer,
+    /// the atlas page of every instance
+    pages: Vec<usize>,
+    /// images drawn right before the frame at each index
+    breaks: Vec<(u32, Range<u32>)>,
+}
+
+impl ImageRenderer {
+    /// images larger than a page get a texture of their own
+    const PAGE_SIZE: u32 = 2048;
+    /// starting number of images, the instance buffer grows past it
+    const IMAGE_CAPACITY: usize = 10000;
+
+    /// draws into the srgb view of `format` a `FrameRenderer` renders through
+    pub fn new(device: &Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let shader = include_wgsl!("shader.wgsl");
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("image shader"),
+            source: shader.source,
+        });
+        let viewport_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("image viewport buffer"),
+            size: mem::size_of::<[f32; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let viewport_bg_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("image viewport layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let viewport_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("image viewport bg"),
+            layout: &viewport_bg_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: viewport_buffer.as_entire_binding(),
+            }],
+        });
+        let atlas = ImageAtlas::new(device, Self::PAGE_SIZE);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("image pipeline layout"),
+            bind_group_layouts: &[&viewport_bg_layout, atlas.layout()],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("image pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc(), ImageData::desc()],
+            },
+            primitive: Vertex::state(),
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: format.add_srgb_suffix(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        let image_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("image instance buffer"),
+            size: (mem::size_of::<ImageData>() * Self::IMAGE_CAPACITY) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            pipeline,
+            viewport_buffer,
+            viewport_bg,
+            atlas,
+            image_buffer,
+            pages: vec![],
+            breaks: vec![],
+        }
+    }
+    /// frees the atlas space of removed images, uploads new images and places every
+    /// frame's image in the frame's bounds as of their last `solve_cameras`
+    pub fn prepare(
+        &mut self,
+        images: &mut ImageStore,
+        frames: &FrameStore,
+        device: &Device,
+        queue: &wgpu::Queue,
+    ) {
+        for index in images.take_removed() {
+            self.atlas.remove(index);
+        }
+        for (index, image) in images.take_pending() {
+            if let Err(TooLarge) = self.atlas.add(device, queue, index, &image) {
+                let (w, h) = image.dimensions();
+                warn!("image {index} of {w}x{h} is larger than a texture can be and is not drawn");
+            }
+        }
+        self.pages.clear();
+        self.breaks.clear();
+        if frames.is_empty() {
+            return;
+        }
+        let window = frames.get(0).data;
+        queue.write_buffer(
+            &self.viewport_buffer,
+            0,
+            bytemuck::cast_slice(&[window.w.pix(), window.h.pix(), 0.0, 0.0]),
+        );
+        let mut instances = vec![];
+        let mut owners = vec![];
+        for (position, &frame) in frames.draw_order().iter().enumerate() {
+            let frame = frame as usize;
+            let Some((image, fit)) = images.get(frame) else {
+                continue;
+            };
+            let (Some((page, uv)), Some(size)) = (self.atlas.uv(image.index()), images.size(image))
+            else {
+                continue;
+            };
+            let area = frames.clipped_bounds(frame);
+            if area.w <= 0.into() || area.h <= 0.into() {
+                continue;
+            }
+            let index = instances.len() as u32;
+            let bounds = frames.screen_bounds(frame);
+            let data = frames.get(frame);
+            instances.push(ImageData {
+                rect: place(fit, size, &bounds),
+                uv,
+                clip: [area.x.pix(), area.y.pix(), area.w.pix(), area.h.pix()],
+                frame: [bounds.x.pix(), bounds.y.pix(), bounds.w.pix(), bounds.h.pix()],
+                radius: data.radius.map(|r| r.pix()),
+                opacity: data.opacity[0],
+            });
+            self.pages.push(page);
+            owners.push((position as u32, index..index + 1, area));
+        }
+        let bytes = mem::size_of_val(instances.as_slice()) as u64;
+        if let Some(buffer) = grown(device, &self.image_buffer, "image instance buffer", bytes) {
+            self.image_buffer = buffer;
+        }
+        queue.write_buffer(&self.image_buffer, 0, bytemuck::cast_slice(&instances));
+        self.breaks = frames.content_breaks(&owners);
+    }
+}
+
+impl FrameContent for ImageRenderer {
+    fn draw_breaks(&self) -> &[(u32, Range<u32>)] {
+        &self.breaks
+    }
+    fn draw<'a: 'rp, 'rp>(&'a self, render_pass: &mut RenderPass<'rp>, images: Range<u32>) {
+        if images.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.viewport_bg, &[]);
+        render_pass.set_vertex_buffer(1, self.image_buffer.slice(..));
+        // one draw for every run of instances on the same page
+        let mut start = images.start;
+        while start < images.end {
+            let page = self.pages[start as usize];
+            let end = (start..images.end)
+                .find(|&i| self.pages[i as usize] != page)
+                .unwrap_or(images.end);
+            if let Some(bind_group) = self.atlas.bind_group(page) {
+                render_pass.set_bind_group(1, bind_group, &[]);
+                render_pass.draw(0..4, start..end);
+            }
+            start = end;
+        }
+    }
+}
+
+/// screen space rect of an image of `size` pixels centered in `bounds`, parts outside of them are clipped
+fn place(fit: ImageFit, size: [u32; 2], bounds: &BBox) -> [f32; 4] {
+    let [bw, bh] = [bounds.w.pix(), bounds.h.pix()];
+    let [iw, ih] = size.map(|s| (s as f32).max(1.0));
+    let [w, h] = match fit {
+        ImageFit::Fill => [bw, bh],
+        ImageFit::Contain => {
+            let scale = f32::min(bw / iw, bh / ih);
+            [iw * scale, ih * scale]
+        }
+        ImageFit::Cover => {
+            let scale = f32::max(bw / iw, bh / ih);
+            [iw * scale, ih * scale]
+        }
+        ImageFit::None => [iw, ih],
+    };
+    [
+        bounds.x.pix() + (bw - w) / 2.0,
+        bounds.y.pix() + (bh - h) / 2.0,
+        w,
+        h,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox([x, y, w, h]: [i32; 4]) -> BBox {
+        BBox {
+            x: x.into(),
+            y: y.into(),
+            w: w.into(),
+            h: h.into(),
+        }
+    }
+
+    /// a 200 by 100 image in bounds wider and taller than it
+    fn placed(fit: ImageFit) -> [[f32; 4]; 2] {
+        [[10, 20, 400, 100], [0, 0, 100, 300]].map(|b| place(fit, [200, 100], &bbox(b)))
+    }
+
+    #[test]
+    fn fill_stretches_to_the_bounds() {
+        assert_eq!(placed(ImageFit::Fill), [[10.0, 20.0, 400.0, 100.0], [0.0, 0.0, 100.0, 300.0]]);
+    }
+
+    #[test]
+    fn contain_fits_inside_and_centers() {
+        assert_eq!(
+            placed(ImageFit::Contain),
+            [[110.0, 20.0, 200.0, 100.0], [0.0, 125.0, 100.0, 50.0]]
+        );
+    }
+
+    #[test]
+    fn cover_overflows_and_centers() {
+        assert_eq!(
+            placed(ImageFit::Cover),
+            [[10.0, -30.0, 400.0, 200.0], [-250.0, 0.0, 600.0, 300.0]]
+        );
+    }
+
+    #[test]
+    fn none_keeps_the_image_size() {
+        assert_eq!(
+            placed(ImageFit::None),
+            [[110.0, 20.0, 200.0, 100.0], [-50.0, 100.0, 200.0, 100.0]]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+// Vertex shader
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    // logical pixels on screen
+    @location(1) rect: vec4<f32>,
+    // xywh in the atlas
+    @location(2) uv: vec4<f32>,
+    // screen space xywh the image is clipped to
+    @location(3) clip: vec4<f32>,
+    @location(4) opacity: f32,
+    // screen space xywh of the frame, the image is clipped to its rounded corners
+    @location(5) frame: vec4<f32>,
+    // [top left, top right, bottom right, bottom left]
+    @location(6) radius: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) screen: vec2<f32>,
+    @location(2) @interpolate(flat) clip: vec4<f32>,
+    @location(3) @interpolate(flat) opacity: f32,
+    @location(4) @interpolate(flat) frame: vec4<f32>,
+    @location(5) @interpolate(flat) radius: vec4<f32>,
+};
+
+// width and height of the window in logical pixels
+@group(0) @binding(0)
+var<uniform> viewport: vec4<f32>;
+
+@group(1) @binding(0)
+var atlas: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+@vertex
+fn vs_main(v: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let corner = v.position / vec2<f32>(2.0, -2.0) + vec2<f32>(0.5, 0.5);
+    let screen = v.rect.xy + corner * v.rect.zw;
+    out.clip_position = vec4<f32>(screen / viewport.xy * vec2<f32>(2.0, -2.0) - vec2<f32>(1.0, -1.0), 1.0, 1.0);
+    out.uv = v.uv.xy + corner * v.uv.zw;
+    out.screen = screen;
+    out.clip = v.clip;
+    out.opacity = v.opacity;
+    out.frame = v.frame;
+    out.radius = v.radius;
+    return out;
+}
+
+/// signed distance to a box at the origin, the same as the frame shader's
+fn rounded_box(p: vec2<f32>, size: vec2<f32>, radius: vec4<f32>) -> f32 {
+    let half = size * 0.5;
+    let c = p - half;
+    let r = min(
+        select(
+            select(radius.x, radius.y, c.x > 0.0),
+            select(radius.w, radius.z, c.x > 0.0),
+            c.y > 0.0,
+        ),
+        min(half.x, half.y),
+    );
+    let q = abs(c) - half + r;
+    return min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0))) - r;
+}
+
+// Fragment shader
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(atlas, atlas_sampler, in.uv, 0.0);
+    // one pixel in logical pixels, derivatives are taken before any discard
+    let px = max((abs(dpdx(in.screen.x)) + abs(dpdy(in.screen.y))) * 0.5, 1e-6);
+    if any(in.screen < in.clip.xy) || any(in.screen >= in.clip.xy + in.clip.zw) {
+        discard;
+    }
+    // the same coverage as the frame's edge, square frames keep hard edges
+    var coverage = 1.0;
+    if any(in.radius > vec4<f32>(0.0)) {
+        let d = rounded_box(in.screen - in.frame.xy, in.frame.zw, in.radius);
+        coverage = clamp(0.5 - d / px, 0.0, 1.0);
+    }
+    if coverage <= 0.0 {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * coverage * in.opacity);
+}
@@ -0,0 +1,95 @@
+use image::RgbaImage;
+
+use crate::handle::{Handle, HandleLike};
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ImageT {}
+
+pub type ImageHandle = Handle<ImageT>;
+
+/// how an image is sized inside of a frame's visible bounds, it is always centered
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum ImageFit {
+    /// stretches the image to the bounds
+    #[default]
+    Fill,
+    /// the largest size that fits the bounds without changing the aspect ratio
+    Contain,
+    /// the smallest size that covers the bounds without changing the aspect ratio, the rest is clipped
+    Cover,
+    /// one pixel of the image is one logical pixel
+    None,
+}
+
+/// sets the image drawn inside of a frame's visible bounds, unset fields keep their value
+#[derive(Clone, Default, Debug)]
+pub struct ImageMessage {
+    /// `Some(None)` removes the image
+    pub image: Option<Option<ImageHandle>>,
+    pub fit: Option<ImageFit>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FrameImage {
+    pub(crate) image: Option<ImageHandle>,
+    pub(crate) fit: ImageFit,
+}
+
+/// loaded images and the image of every frame, drawn from an atlas by `ImageRenderer`
+#[derive(Default)]
+pub struct ImageStore {
+    /// width and height of every image
+    sizes: Vec<[u32; 2]>,
+    /// images that are not in the atlas yet
+    pending: Vec<(usize, RgbaImage)>,
+    /// images whose atlas space is not freed yet
+    removed: Vec<usize>,
+    frames: Vec<Option<FrameImage>>,
+}
+
+impl ImageStore {
+    pub fn add(&mut self, image: RgbaImage) -> ImageHandle {
+        let index = self.sizes.len();
+        self.sizes.push([image.width(), image.height()]);
+        self.pending.push((index, image));
+        ImageHandle::new(index)
+    }
+    pub(crate) fn size(&self, image: ImageHandle) -> Option<[u32; 2]> {
+        self.sizes.get(image.index()).copied()
+    }
+    pub(crate) fn take_pending(&mut self) -> Vec<(usize, RgbaImage)> {
+        std::mem::take(&mut self.pending)
+    }
+    /// frees the image's atlas space, frames still showing it draw nothing
+    pub fn remove(&mut self, image: ImageHandle) {
+        let index = image.index();
+        self.pending.retain(|(i, _)| *i != index);
+        self.removed.push(index);
+    }
+    pub(crate) fn take_removed(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.removed)
+    }
+    /// returns whether the frame's image or its fit changed
+    pub fn update(&mut self, frame: usize, msg: ImageMessage) -> bool {
+        if self.frames.len() <= frame {
+            self.frames.resize(frame + 1, None);
+        }
+        let old = self.frames[frame];
+        let ImageMessage { image, fit } = msg;
+        let f = self.frames[frame].get_or_insert(FrameImage {
+            image: None,
+            fit: ImageFit::default(),
+        });
+        if let Some(image) = image {
+            f.image = image;
+        }
+        if let Some(fit) = fit {
+            f.fit = fit;
+        }
+        old.as_ref() != Some(f)
+    }
+    pub(crate) fn get(&self, frame: usize) -> Option<(ImageHandle, ImageFit)> {
+        let f = self.frames.get(frame)?.as_ref()?;
+        Some((f.image?, f.fit))
+    }
+}
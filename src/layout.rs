@@ -0,0 +1,745 @@
+use std::sync::mpsc;
+
+use bytemuck::Zeroable;
+use serde::Serialize;
+
+use crate::{
+    component::{ComponentBuilder, State},
+    error::LayoutError,
+    floating::FloatingRenderer,
+    frame::{FrameData, FrameHandle, FrameStore},
+    grid::{GridHandle, GridRenderer},
+    handle::HandleLike,
+    images::ImageStore,
+    manager::{BBox, MarginBox, Rect},
+    render_actor::{FrameMessage, UpdateMessage},
+    scroll::ScrollRenderer,
+    text::TextStore,
+    update_queue::front,
+    Component,
+};
+
+/// everything needed to solve the layout, without any gpu resources
+#[derive(Default)]
+pub struct Layout {
+    pub(crate) frames: FrameStore,
+    pub(crate) grids: GridRenderer,
+    pub(crate) floating: FloatingRenderer,
+    pub(crate) scroll: ScrollRenderer,
+    pub(crate) text: TextStore,
+    pub(crate) images: ImageStore,
+    grid_to_frame_map: Vec<FrameHandle>,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self {
+            frames: FrameStore::default(),
+            grids: GridRenderer::new(),
+            floating: FloatingRenderer::new(),
+            scroll: ScrollRenderer::new(),
+            text: TextStore::default(),
+            images: ImageStore::default(),
+            grid_to_frame_map: vec![],
+        }
+    }
+    fn new_frame(&mut self, f: FrameMessage, camera_index: u32) -> usize {
+        let index = self.frames.add(FrameData {
+            camera_index,
+            ..FrameData::zeroed()
+        });
+        self.modify_frame(index, f);
+        index
+    }
+    fn modify_frame(&mut self, index: usize, f: FrameMessage) {
+        let FrameMessage {
+            size,
+            color,
+            margin,
+            clip,
+            aspect_ratio,
+            radius,
+            border,
+            gradient,
+            shadow,
+            backdrop_blur,
+            z_index,
+            layer,
+            opacity,
+            opacity_group,
+        } = f;
+        if let Some(aspect) = aspect_ratio {
+            self.frames.update_aspect(index, aspect);
+        }
+        if let Some(size) = size {
+            self.frames.update(index, &size);
+        }
+        if let Some(color) = color {
+            self.frames.update_color(index, color);
+        }
+        if let Some(margin) = margin {
+            self.frames.update_margin(index, margin);
+        }
+        if let Some(clip) = clip {
+            self.frames.update_clip(index, clip);
+        }
+        if let Some(radius) = radius {
+            self.frames.update_radius(index, radius);
+        }
+        if let Some(border) = border {
+            self.frames.update_border(index, border);
+        }
+        if let Some(gradient) = gradient {
+            self.frames.update_gradient(index, gradient);
+        }
+        if let Some(shadow) = shadow {
+            self.frames.update_shadow(index, shadow);
+        }
+        if let Some(blur) = backdrop_blur {
+            self.frames.update_backdrop_blur(index, blur);
+        }
+        if let Some(z_index) = z_index {
+            self.frames.update_z_index(index, z_index);
+        }
+        if let Some(layer) = layer {
+            self.frames.update_layer(index, layer);
+        }
+        if opacity.is_some() || opacity_group.is_some() {
+            self.frames.update_opacity(index, opacity, opacity_group);
+        }
+    }
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.frames.update(
+            0,
+            &Rect {
+                x: 0,
+                y: 0,
+                w: width,
+                h: height,
+            }
+            .into(),
+        );
+    }
+    /// applies the layout part of `msg`, messages for the gpu are ignored,
+    /// returns whether anything drawn changed, frames count as changed until their
+    /// changes are taken by the next prepare
+    pub fn apply(&mut self, msg: UpdateMessage) -> Result<bool, LayoutError> {
+        let changed = match msg {
+            UpdateMessage::ModifyFrame(h, f) => {
+                self.modify_frame(h.index(), f);
+                self.frames.has_changes()
+            }
+            UpdateMessage::NewFrame(grid, x, y, f, frame_handle) => {
+                let camera = match self.grid_to_frame_map.get(grid.index()) {
+                    Some(parent) => FrameStore::camera(parent.index()),
+                    None => FrameStore::WINDOW_CAMERA,
+                };
+                self.new_frame(f, camera);
+                self.grids
+                    .add_frame(&mut self.frames, grid, frame_handle, x, y)?;
+                true
+            }
+            UpdateMessage::NewFloatingFrame(size, f) => {
+                let index = self.new_frame(f, FrameStore::WINDOW_CAMERA);
+                // the root frame follows the window instead of its rect
+                match index {
+                    0 => {
+                        let ctx = self.frames.unit_context();
+                        self.frames.update(0, &size.solve(&BBox::zeroed(), &ctx))
+                    }
+                    _ => self.floating.add(FrameHandle::new(index), size),
+                }
+                true
+            }
+            UpdateMessage::NewAnchoredFrame(target, anchor, size, f) => {
+                let index = self.new_frame(f, FrameStore::WINDOW_CAMERA);
+                self.floating
+                    .add_anchored(FrameHandle::new(index), target, anchor, size);
+                true
+            }
+            UpdateMessage::ModifyGrid(_grid, _g) => false,
+            UpdateMessage::NewGrid(_grid_index, grid_builder) => {
+                self.grid_to_frame_map.push(grid_builder.parent());
+                self.grids.add(grid_builder.build());
+                true
+            }
+            UpdateMessage::NewScroll(grid, extents) => {
+                let frame = self.grid_to_frame_map[grid.index()];
+                self.frames.update_clip(frame.index(), true);
+                self.scroll.add(grid, frame, extents);
+                true
+            }
+            UpdateMessage::Scroll(grid, command) => self.scroll.command(grid, command),
+            UpdateMessage::ScrollInput(input) => self.scroll.input(&self.frames, input),
+            // the pointer only picks the scroll container keys go to
+            UpdateMessage::Pointer(x, y) => {
+                self.scroll.pointer(&self.frames, x, y);
+                false
+            }
+            // text and images that waited for their font or image are drawn now
+            UpdateMessage::NewFont(_font, font) => {
+                self.text.add_font(font);
+                true
+            }
+            UpdateMessage::ModifyText(h, t) => self.text.update(h.index(), t),
+            UpdateMessage::NewImage(_image, image) => {
+                self.images.add(image);
+                true
+            }
+            UpdateMessage::ModifyImage(h, i) => self.images.update(h.index(), i),
+            UpdateMessage::RemoveImage(image) => {
+                self.images.remove(image);
+                true
+            }
+            UpdateMessage::RootUnit(rem) => {
+                self.frames.set_rem(rem.into());
+                self.frames.has_changes()
+            }
+            UpdateMessage::Direction(direction) => {
+                self.frames.set_direction(direction);
+                self.frames.has_changes()
+            }
+            UpdateMessage::ResizeWindow(logical, _) => {
+                self.resize(logical.width, logical.height);
+                self.frames.has_changes()
+            }
+            _ => false,
+        };
+        Ok(changed)
+    }
+    /// grids first so floating frames and scroll containers see the new bounds,
+    /// returns the frames a breakpoint change had to hide
+    pub fn solve(&mut self) -> Vec<LayoutError> {
+        let errors = self.grids.prepare(&mut self.frames);
+        self.scroll.prepare(&self.grids, &mut self.frames);
+        self.floating.prepare(&mut self.frames);
+        self.frames.solve_cameras();
+        self.frames.solve_order();
+        errors
+    }
+    pub fn snapshot(&self) -> LayoutSnapshot {
+        let mut frames: Vec<FrameSnapshot> = self
+            .frames
+            .data()
+            .iter()
+            .enumerate()
+            .map(|(handle, f)| FrameSnapshot {
+                handle,
+                parent: match f.camera_index {
+                    FrameStore::WINDOW_CAMERA => None,
+                    c => Some(c as usize - 1),
+                },
+                grid: None,
+                cell: None,
+                bounds: f.data.into(),
+                margin: f.margin.into(),
+                border: f.border.into(),
+                color: f.color.into(),
+            })
+            .collect();
+        let grids = (0..self.grids.len())
+            .map(|grid| {
+                let data = self.grids.get(GridHandle::new(grid));
+                for (frame, cell) in data.cells() {
+                    frames[frame.index()].grid = Some(grid);
+                    frames[frame.index()].cell = Some(cell);
+                }
+                let [columns, rows] = data.track_counts();
+                let [width, height] = data.content_size().map(|v| v.pix());
+                GridSnapshot {
+                    handle: grid,
+                    parent: data.parent().index(),
+                    columns,
+                    rows,
+                    content: [width, height],
+                }
+            })
+            .collect();
+        LayoutSnapshot { frames, grids }
+    }
+}
+
+/// solved layout of every frame and grid in creation order, sizes are in logical pixels
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LayoutSnapshot {
+    pub frames: Vec<FrameSnapshot>,
+    pub grids: Vec<GridSnapshot>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FrameSnapshot {
+    pub handle: usize,
+    pub parent: Option<usize>,
+    pub grid: Option<usize>,
+    /// column and row in `grid`
+    pub cell: Option<[usize; 2]>,
+    pub bounds: RectSnapshot,
+    pub margin: MarginSnapshot,
+    /// stroke widths, after right to left frames swapped them
+    pub border: MarginSnapshot,
+    pub color: [u8; 4],
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GridSnapshot {
+    pub handle: usize,
+    pub parent: usize,
+    pub columns: usize,
+    pub rows: usize,
+    /// width and height of the laid out tracks
+    pub content: [f32; 2],
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct RectSnapshot {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl From<BBox> for RectSnapshot {
+    fn from(b: BBox) -> Self {
+        Self {
+            x: b.x.pix(),
+            y: b.y.pix(),
+            w: b.w.pix(),
+            h: b.h.pix(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct MarginSnapshot {
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl From<MarginBox> for MarginSnapshot {
+    fn from(m: MarginBox) -> Self {
+        Self {
+            top: m.top.pix(),
+            bottom: m.bottom.pix(),
+            left: m.left.pix(),
+            right: m.right.pix(),
+        }
+    }
+}
+
+impl LayoutSnapshot {
+    /// pretty printed json, the field and frame order is stable for golden files
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("layout snapshots always serialize")
+    }
+}
+
+impl Layout {
+    /// builds `App` in a `width` by `height` window and solves its layout
+    pub(crate) fn solved<App: State<Param = ()>>(width: u32, height: u32) -> Self {
+        let (send, recv) = mpsc::channel();
+        let mut builder = ComponentBuilder::new(send.clone(), front::UpdateQueue::new(&send));
+        let _app: Component<App> = builder.send_app(Rect {
+            x: 0,
+            y: 0,
+            w: width as i32,
+            h: height as i32,
+        });
+        let mut layout = Layout::new();
+        for msg in recv.try_iter() {
+            // placement errors are logged by the grid and leave the frame unplaced
+            let _ = layout.apply(msg);
+        }
+        layout.resize(width, height);
+        layout.solve();
+        layout
+    }
+}
+
+/// builds `App` in a `width` by `height` window and solves its layout without a gpu
+pub fn layout_snapshot<App: State<Param = ()>>(width: u32, height: u32) -> LayoutSnapshot {
+    Layout::solved::<App>(width, height).snapshot()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use super::*;
+    use crate::{
+        component::{Builder, UpdateQueue},
+        floating::{Align, Anchor, Side},
+        grid::{XName, YName},
+        frame::Border,
+        grid::Direction,
+        manager::Borders,
+        units::{max, UserUnits::{self, *}, VUnit},
+    };
+
+    /// compares against `tests/snapshots/<name>.json`, `XGRID_UPDATE_SNAPSHOTS=1` rewrites it
+    fn assert_golden(name: &str, snapshot: &LayoutSnapshot) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(format!("{name}.json"));
+        let json = snapshot.to_json() + "\n";
+        if env::var_os("XGRID_UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &json).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!("{}: {e}, run with XGRID_UPDATE_SNAPSHOTS=1", path.display())
+        });
+        assert_eq!(json, expected, "{name} doesn't match {}", path.display());
+    }
+
+    /// applies `msgs` the way the render thread does after the app is built
+    fn solved_with<App: State<Param = ()>>(
+        width: u32,
+        height: u32,
+        msgs: impl IntoIterator<Item = UpdateMessage>,
+    ) -> LayoutSnapshot {
+        let mut layout = Layout::solved::<App>(width, height);
+        for msg in msgs {
+            layout.apply(msg).unwrap();
+        }
+        layout.solve();
+        layout.snapshot()
+    }
+
+    struct Placement;
+    impl State for Placement {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.widths().template("100px 1fr 2fr").unwrap().build();
+            g.heights().template("50px 1fr").unwrap().build();
+            let g = builder.grid(g);
+            builder.frame::<()>((), g, Some(XName::new(2)), Some(YName::new(1)));
+            builder.frame::<()>((), g, None, Some(YName::new(1)));
+            for _ in 0..3 {
+                builder.frame::<()>((), g, None, None);
+            }
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn grid_placement() {
+        assert_golden("grid_placement", &layout_snapshot::<Placement>(400, 300));
+    }
+
+    struct Spacers;
+    impl State for Spacers {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.widths()
+                .add(Pixel(50))
+                .add(Ratio(0.25))
+                .add(Fraction(1))
+                .add(Rem(2.0))
+                .build();
+            g.heights()
+                .add(max(Pixel(20), Ratio(0.1)))
+                .template("repeat(2, 1fr)")
+                .unwrap()
+                .build();
+            let g = builder.grid(g);
+            for _ in 0..6 {
+                builder.frame::<()>((), g, None, None);
+            }
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn spacers() {
+        assert_golden("spacers", &layout_snapshot::<Spacers>(640, 480));
+    }
+
+    struct Margins;
+    impl State for Margins {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.widths().template("1fr 1fr").unwrap().build();
+            g.heights().template("1fr").unwrap().build();
+            let g = builder.grid(g);
+            builder.frame::<()>((), g, None, None);
+            builder.frame::<()>((), g, None, None);
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    fn margin(
+        top: UserUnits,
+        bottom: UserUnits,
+        left: UserUnits,
+        right: UserUnits,
+    ) -> FrameMessage {
+        FrameMessage {
+            margin: Some(Borders { top, bottom, left, right }),
+            ..FrameMessage::default()
+        }
+    }
+
+    #[test]
+    fn margins() {
+        let snapshot = solved_with::<Margins>(
+            400,
+            200,
+            [
+                UpdateMessage::ModifyFrame(
+                    FrameHandle::new(1),
+                    margin(Pixel(10), Pixel(20), Pixel(5), Zero),
+                ),
+                UpdateMessage::ModifyFrame(
+                    FrameHandle::new(2),
+                    margin(Ratio(0.1), Zero, Ratio(0.25), Rem(1.0)),
+                ),
+            ],
+        );
+        assert_golden("margins", &snapshot);
+    }
+
+    struct Floating;
+    impl State for Floating {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let target: Component<()> =
+                builder.floating_frame((), Rect { x: 100, y: 80, w: 120, h: 40 });
+            let below = Anchor {
+                side: Side::Below,
+                align: Align::Center,
+            };
+            let size = Rect { x: 0, y: 4, w: 60, h: 30 };
+            builder.anchored_frame::<(), _, _>((), &target, below, size);
+            let inside = Anchor {
+                side: Side::Inside,
+                align: Align::End,
+            };
+            let size = Rect { x: 0, y: 0, w: 20, h: 20 };
+            builder.anchored_frame::<(), _, _>((), &target, inside, size);
+            // flips above since it doesn't fit below the window's bottom edge
+            let edge: Component<()> =
+                builder.floating_frame((), Rect { x: 300, y: 260, w: 50, h: 30 });
+            builder.anchored_frame::<(), _, _>((), &edge, below, Rect { x: 0, y: 0, w: 40, h: 40 });
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn floating_and_anchored() {
+        assert_golden("floating", &layout_snapshot::<Floating>(400, 300));
+    }
+
+    struct Breakpoints;
+    impl State for Breakpoints {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.widths().template("1fr").unwrap().build();
+            g.heights().template("repeat(3, 50px)").unwrap().build();
+            g.breakpoint(600)
+                .widths()
+                .template("repeat(3, 1fr)")
+                .unwrap()
+                .build();
+            let g = builder.grid(g);
+            for _ in 0..3 {
+                builder.frame::<()>((), g, None, None);
+            }
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn breakpoints() {
+        assert_golden("breakpoint_narrow", &layout_snapshot::<Breakpoints>(400, 300));
+        assert_golden("breakpoint_wide", &layout_snapshot::<Breakpoints>(800, 300));
+    }
+
+    struct RightToLeft;
+    impl State for RightToLeft {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.direction(Direction::Rtl);
+            g.widths().template("100px 1fr").unwrap().build();
+            g.heights().template("1fr").unwrap().build();
+            let g = builder.grid(g);
+            let first: Component<()> = builder.frame((), g, None, None);
+            builder.frame::<()>((), g, None, None);
+            let below = Anchor {
+                side: Side::Below,
+                align: Align::Start,
+            };
+            let size = Rect { x: 10, y: 0, w: 40, h: 20 };
+            builder.anchored_frame::<(), _, _>((), &first, below, size);
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn right_to_left() {
+        let border = |width: i32| Border {
+            width: width.into(),
+            color: crate::Color::BLACK,
+        };
+        let snapshot = solved_with::<RightToLeft>(
+            400,
+            200,
+            [
+                UpdateMessage::ModifyFrame(
+                    FrameHandle::new(1),
+                    FrameMessage {
+                        border: Some(Borders {
+                            top: border(0),
+                            bottom: border(0),
+                            left: border(4),
+                            right: border(1),
+                        }),
+                        ..margin(Zero, Zero, Pixel(8), Pixel(2))
+                    },
+                ),
+            ],
+        );
+        assert_golden("right_to_left", &snapshot);
+    }
+
+    #[test]
+    fn relative_styles_follow_the_bounds() {
+        let mut layout = Layout::solved::<Margins>(400, 100);
+        let style = FrameMessage {
+            radius: Some(crate::Corners {
+                top_left: Ratio(0.5),
+                top_right: Pixel(4),
+                bottom_right: Zero,
+                bottom_left: Rem(1.0),
+            }),
+            border: Some(Borders {
+                top: Border {
+                    width: Ratio(0.1),
+                    color: crate::Color::BLACK,
+                },
+                bottom: Border {
+                    width: Zero,
+                    color: crate::Color::BLACK,
+                },
+                left: Border {
+                    width: Ratio(0.1),
+                    color: crate::Color::BLACK,
+                },
+                right: Border {
+                    width: Pixel(1),
+                    color: crate::Color::BLACK,
+                },
+            }),
+            shadow: Some(crate::Shadow {
+                offset: [Ratio(0.1), Ratio(0.1)],
+                blur: Ratio(0.2),
+                spread: Pixel(2),
+                color: crate::Color::BLACK,
+            }),
+            backdrop_blur: Some(Ratio(0.5)),
+            ..FrameMessage::default()
+        };
+        layout.apply(UpdateMessage::ModifyFrame(FrameHandle::new(1), style)).unwrap();
+        let px = |v: VUnit| v.pix();
+        let frame = *layout.frames.get(1);
+        // 200 by 100, the smaller side is 100
+        assert_eq!(frame.radius.map(px), [50.0, 4.0, 0.0, 16.0]);
+        assert_eq!([frame.border.top, frame.border.left].map(px), [10.0, 20.0]);
+        assert_eq!(frame.shadow.map(px), [20.0, 10.0, 20.0, 2.0]);
+        assert_eq!(px(frame.backdrop_blur), 50.0);
+        // a resize solves them again
+        layout.resize(400, 60);
+        layout.solve();
+        let frame = *layout.frames.get(1);
+        assert_eq!(frame.radius.map(px), [30.0, 4.0, 0.0, 16.0]);
+        assert_eq!(px(frame.backdrop_blur), 30.0);
+    }
+
+    #[test]
+    fn apply_reports_only_changes() {
+        let mut layout = Layout::solved::<Margins>(400, 100);
+        // what the renderers take on every prepare
+        let settle = |layout: &mut Layout| {
+            layout.solve();
+            layout.frames.take_changed();
+            layout.frames.take_changed_cameras();
+            layout.frames.take_gradients();
+        };
+        settle(&mut layout);
+        let red = || {
+            let color = FrameMessage {
+                color: Some(crate::Color::rgb(255, 0, 0)),
+                ..FrameMessage::default()
+            };
+            UpdateMessage::ModifyFrame(FrameHandle::new(1), color)
+        };
+        assert!(layout.apply(red()).unwrap());
+        settle(&mut layout);
+        assert!(!layout.apply(red()).unwrap());
+        assert!(!layout.apply(UpdateMessage::Pointer(10.0, 10.0)).unwrap());
+        let text = || {
+            let text = crate::TextMessage {
+                text: Some("hi".into()),
+                ..Default::default()
+            };
+            UpdateMessage::ModifyText(FrameHandle::new(1), text)
+        };
+        assert!(layout.apply(text()).unwrap());
+        assert!(!layout.apply(text()).unwrap());
+        assert!(!layout.apply(UpdateMessage::RootUnit(16.0)).unwrap());
+        assert!(layout.apply(UpdateMessage::RootUnit(20.0)).unwrap());
+    }
+
+    struct Viewport;
+    impl State for Viewport {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.widths().template("10vw 2rem 1fr").unwrap().build();
+            g.heights().template("50vh 1fr").unwrap().build();
+            let g = builder.grid(g);
+            for _ in 0..2 {
+                builder.frame::<()>((), g, None, Some(YName::new(0)));
+            }
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn viewport_units_follow_the_window() {
+        let size = |layout: &Layout, i: usize| {
+            let b = &layout.frames.get(i).data;
+            [b.w.pix(), b.h.pix()]
+        };
+        let mut layout = Layout::solved::<Viewport>(400, 300);
+        assert_eq!([size(&layout, 1), size(&layout, 2)], [[40.0, 150.0], [32.0, 150.0]]);
+        let resize = UpdateMessage::ResizeWindow(winit::dpi::LogicalSize::new(800, 600), 1.0);
+        assert!(layout.apply(resize).unwrap());
+        layout.solve();
+        assert_eq!([size(&layout, 1), size(&layout, 2)], [[80.0, 300.0], [32.0, 300.0]]);
+        assert!(layout.apply(UpdateMessage::RootUnit(24.0)).unwrap());
+        layout.solve();
+        assert_eq!(size(&layout, 2), [48.0, 300.0]);
+    }
+}
@@ -3,23 +3,50 @@ use wasm_bindgen::prelude::*;
 
 extern crate proc_macro;
 
+pub(crate) mod color;
 pub(crate) mod component;
+pub(crate) mod debug;
+pub(crate) mod error;
 pub(crate) mod events;
+pub(crate) mod floating;
 pub(crate) mod frame;
 pub(crate) mod grid;
 pub(crate) mod handle;
+pub(crate) mod headless;
+pub(crate) mod images;
+pub(crate) mod layout;
 pub(crate) mod manager;
 pub(crate) mod observer;
 pub(crate) mod render_actor;
+pub(crate) mod scroll;
+pub(crate) mod text;
 pub(crate) mod units;
 pub(crate) mod update_queue;
 
+pub use color::Color;
 pub use component::{Builder, Component, SystemEvents, UpdateQueue};
 pub use component::{Interaction, State};
+pub use error::{
+    ColorError, FontError, HeadlessError, ImageError, LayoutError, TemplateError,
+    TemplateErrorKind,
+};
 pub use events::{ButtonState, KeyboardEvent, KeyboardKey, MouseButton, MouseEvent};
-pub use frame::{FrameData, FrameHandle};
-pub use manager::{run, RenderManager, Rect};
+pub use floating::{Align, Anchor, Side};
+pub use frame::{
+    AspectRatio, Border, ColorStop, FrameData, FrameHandle, Gradient, Layer, Shadow,
+};
+pub use grid::Direction;
+pub use headless::{render_png, render_rgba, write_png, HeadlessRenderer};
+pub use images::{ImageFit, ImageHandle, ImageMessage};
+pub use layout::{
+    layout_snapshot, FrameSnapshot, GridSnapshot, Layout, LayoutSnapshot, MarginSnapshot,
+    RectSnapshot,
+};
+pub use manager::{run, run_multisampled, Borders, Corners, RedrawMode, RenderManager, Rect};
 pub use observer::{EventDispatcher, Subscriber};
 pub use render_actor::FrameMessage;
+pub use scroll::{Scroll, ScrollExtents};
+pub use text::{FontHandle, TextMessage};
 pub use units::UserUnits::*;
+pub use units::{max, min, UserUnits};
 pub use update_queue::back::UpdateMsg;
@@ -33,10 +33,10 @@ impl State for Div {
             true => Self::DA,
             false => Self::UA,
         };
-        queue.push(UpdateMsg::Frame(FrameMessage {
-            color: Some(self.color),
+        queue.push(UpdateMsg::Frame(Box::new(FrameMessage {
+            color: Some(self.color.into()),
             ..FrameMessage::default()
-        }));
+        })));
     }
 }
 impl Subscriber<i32> for Div {
@@ -61,22 +61,8 @@ impl State for App {
         let mut g = builder.grid_builder();
         let [_yn] = g.heights().add_expanding(Fraction(1)).assign();
         g.widths()
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
-            .add(Fraction(1))
+            .template("repeat(16, 1fr)")
+            .unwrap()
             .build();
         let g = builder.grid(g);
         let state_event = builder.event_dispatcher();
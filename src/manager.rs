@@ -8,28 +8,32 @@ use bytemuck::{Pod, Zeroable};
 use log::{warn, debug};
 use wgpu::{util::DeviceExt, SurfaceError};
 use winit::{
-    dpi::{LogicalSize, PhysicalSize},
-    event::{ElementState, Event, KeyEvent, WindowEvent},
-    event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget},
+    dpi::{LogicalPosition, LogicalSize, PhysicalSize},
+    event::{ElementState, Event, KeyEvent, MouseScrollDelta, WindowEvent},
+    event_loop::{EventLoopBuilder, EventLoopProxy, EventLoopWindowTarget},
     keyboard::{Key, NamedKey},
     window::{Window, WindowBuilder},
 };
 
 use crate::{
     component::{self, ComponentBuilder, State},
+    debug::DebugOverlay,
     events::MouseEvent,
     frame::{FrameHandle, FrameRenderer},
-    grid::GridRenderer,
     handle::HandleLike,
-    render_actor::{FrameMessage, UpdateMessage},
-    units::VUnit,
+    images::ImageRenderer,
+    layout::Layout,
+    render_actor::{RenderEvent, UpdateMessage},
+    scroll::{ScrollInput, ScrollKey, LINE_PIXELS},
+    text::TextRenderer,
+    units::{UnitContext, UserUnits, VUnit},
     update_queue::{
         self, back::Update, front
     },
     ButtonState, Component, MouseButton,
 };
 
-const VERTICES: &[Vertex] = &[
+pub(crate) const VERTICES: &[Vertex] = &[
     Vertex {
         position: [-1., 1.],
     }, // A
@@ -91,12 +95,52 @@ pub struct BBox {
     pub(crate) h: VUnit,
 }
 
-pub struct Rect<T: Into<VUnit>> {
+impl BBox {
+    pub(crate) fn intersect(&self, other: &BBox) -> BBox {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let w = (self.x + self.w).min(other.x + other.w) - x;
+        let h = (self.y + self.h).min(other.y + other.h) - y;
+        BBox {
+            x,
+            y,
+            w: w.max(0.into()),
+            h: h.max(0.into()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Rect<T> {
     pub x: T,
     pub y: T,
     pub w: T,
     pub h: T,
 }
+
+impl<T: Into<UserUnits>> Rect<T> {
+    pub(crate) fn units(self) -> Rect<UserUnits> {
+        let Self { x, y, w, h } = self;
+        Rect {
+            x: x.into(),
+            y: y.into(),
+            w: w.into(),
+            h: h.into(),
+        }
+    }
+}
+
+impl Rect<UserUnits> {
+    /// `x` and `w` are relative to the width of `within`, `y` and `h` to its height
+    pub(crate) fn solve(&self, within: &BBox, ctx: &UnitContext) -> BBox {
+        BBox {
+            x: within.x + self.x.solve(within.w, ctx),
+            y: within.y + self.y.solve(within.h, ctx),
+            w: self.w.solve(within.w, ctx),
+            h: self.h.solve(within.h, ctx),
+        }
+    }
+}
 impl<T: Into<VUnit>> Into<BBox> for Rect<T> {
     fn into(self) -> BBox {
         let Self { x, y, w, h } = self;
@@ -119,13 +163,31 @@ pub struct MarginBox {
     pub right: VUnit,
 }
 
-pub struct Borders<T: Into<VUnit>> {
+#[derive(Clone, Debug)]
+pub struct Borders<T> {
     pub top: T,
     pub bottom: T,
     pub left: T,
     pub right: T,
 }
 
+impl Borders<UserUnits> {
+    /// `top` and `bottom` are relative to the height of `bounds`, `left` and `right` to its width,
+    /// `rtl` swaps `left` and `right`
+    pub(crate) fn solve(&self, bounds: &BBox, rtl: bool, ctx: &UnitContext) -> MarginBox {
+        let (left, right) = match rtl {
+            true => (&self.right, &self.left),
+            false => (&self.left, &self.right),
+        };
+        MarginBox {
+            top: self.top.solve(bounds.h, ctx),
+            bottom: self.bottom.solve(bounds.h, ctx),
+            left: left.solve(bounds.w, ctx),
+            right: right.solve(bounds.w, ctx),
+        }
+    }
+}
+
 impl<T: Into<VUnit>> Into<MarginBox> for Borders<T> {
     fn into(self) -> MarginBox {
         let Self {
@@ -142,6 +204,83 @@ impl<T: Into<VUnit>> Into<MarginBox> for Borders<T> {
         }
     }
 }
+/// per corner values, like the radii of a frame
+#[derive(Clone, Debug)]
+pub struct Corners<T> {
+    pub top_left: T,
+    pub top_right: T,
+    pub bottom_right: T,
+    pub bottom_left: T,
+}
+
+impl Corners<UserUnits> {
+    /// every radius is relative to the smaller side of `bounds`
+    pub(crate) fn solve(&self, bounds: &BBox, ctx: &UnitContext) -> [VUnit; 4] {
+        let side = bounds.w.min(bounds.h);
+        [
+            &self.top_left,
+            &self.top_right,
+            &self.bottom_right,
+            &self.bottom_left,
+        ]
+        .map(|r| r.solve(side, ctx))
+    }
+}
+
+impl<T: Into<VUnit>> From<Corners<T>> for [VUnit; 4] {
+    fn from(corners: Corners<T>) -> Self {
+        let Corners {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        } = corners;
+        [
+            top_left.into(),
+            top_right.into(),
+            bottom_right.into(),
+            bottom_left.into(),
+        ]
+    }
+}
+
+/// when the window is drawn again
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum RedrawMode {
+    /// only after something changed, while an animation is running or when the os asks for it
+    #[default]
+    OnDemand,
+    /// at the display's refresh rate, for games and other apps that change every frame
+    Continuous,
+}
+
+/// the highest sample count up to `requested` that `format` supports on `adapter`,
+/// with the features a count other than 1 or 4 needs
+pub(crate) fn supported_samples(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+    requested: u32,
+) -> (u32, wgpu::Features) {
+    let flags = adapter.get_texture_format_features(format).flags;
+    let specific = wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+    let count = [16, 8, 4, 2, 1]
+        .into_iter()
+        .filter(|&c| c <= requested.max(1))
+        .find(|&c| match c {
+            1 => true,
+            4 => flags.sample_count_supported(c),
+            _ => adapter.features().contains(specific) && flags.sample_count_supported(c),
+        })
+        .unwrap_or(1);
+    if count != requested.max(1) {
+        warn!("{format:?} doesn't support {requested} samples per pixel, using {count}");
+    }
+    match count {
+        1 | 4 => (count, wgpu::Features::empty()),
+        _ => (count, specific),
+    }
+}
+
 pub struct RenderManager<'a> {
     vertex_buffer: wgpu::Buffer,
     surface: wgpu::Surface<'a>,
@@ -151,21 +290,30 @@ pub struct RenderManager<'a> {
     base_handle: FrameHandle,
     msg_send: mpsc::Sender<UpdateMessage>,
     msg_recv: mpsc::Receiver<UpdateMessage>,
-    grid_to_frame_map: Vec<FrameHandle>,
     config: wgpu::SurfaceConfiguration,
     device: wgpu::Device,
     queue: wgpu::Queue,
     frame_renderer: FrameRenderer,
-    grid_renderer: GridRenderer,
-    proxy: EventLoopProxy<()>,
+    text_renderer: TextRenderer,
+    image_renderer: ImageRenderer,
+    layout: Layout,
+    overlay: DebugOverlay,
+    proxy: EventLoopProxy<RenderEvent>,
+    redraw_mode: RedrawMode,
+    /// animations that keep the window redrawing until they end
+    animations: usize,
+    /// a redraw was requested from the window and hasn't been prepared yet
+    redraw_requested: bool,
 }
 
 impl<'a> RenderManager<'a> {
+    /// `sample_count` falls back to the highest one the surface supports
     pub async fn new(
         window: &'a Window,
         send: mpsc::Sender<UpdateMessage>,
         recv: mpsc::Receiver<UpdateMessage>,
-        proxy: winit::event_loop::EventLoopProxy<()>,
+        proxy: winit::event_loop::EventLoopProxy<RenderEvent>,
+        sample_count: u32,
     ) -> (update_queue::front::UpdateQueue, Self) {
         let size_pixels = window.inner_size();
         let size: LogicalSize<u32> = size_pixels.to_logical(window.scale_factor());
@@ -189,11 +337,32 @@ impl<'a> RenderManager<'a> {
             .await
             .unwrap();
 
+        let surface_caps = surface.get_capabilities(&adapter);
+
+        // colors are blended in linear space and encoded by an srgb view of the surface,
+        // without one the frame renderer encodes them itself
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .unwrap_or(surface_caps.formats[0]);
+        let srgb_views = adapter.get_downlevel_capabilities().flags;
+        let view_formats = match surface_format.is_srgb() {
+            false if srgb_views.contains(wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS) => {
+                vec![surface_format.add_srgb_suffix()]
+            }
+            _ => vec![],
+        };
+        let view_format = view_formats.first().copied().unwrap_or(surface_format);
+        let (sample_count, required_features) =
+            supported_samples(&adapter, surface_format.add_srgb_suffix(), sample_count);
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
-                    required_features: wgpu::Features::empty(),
+                    required_features,
                     // WebGL doesn't support all of wgpu's features, so if
                     // we're building for the web we'll have to disable some.
                     required_limits: if cfg!(target_arch = "wasm32") {
@@ -207,16 +376,10 @@ impl<'a> RenderManager<'a> {
             .await
             .unwrap();
 
-        let surface_caps = surface.get_capabilities(&adapter);
-
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .find(|f| matches!(f, wgpu::TextureFormat::Rgba8Unorm))
-            .unwrap_or(surface_caps.formats[0]);
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            // backdrop blurs copy what is already drawn
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC),
             format: surface_format,
             width: size_pixels.width as u32,
             height: size_pixels.height as u32,
@@ -226,7 +389,7 @@ impl<'a> RenderManager<'a> {
                 .find(|&&e| e == wgpu::PresentMode::AutoVsync)
                 .unwrap_or(&surface_caps.present_modes[0]),
             alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
+            view_formats,
             desired_maximum_frame_latency: 2,
         };
         surface.configure(&device, &config);
@@ -255,24 +418,31 @@ impl<'a> RenderManager<'a> {
             usage: wgpu::BufferUsages::VERTEX,
         });
         let update_queue = front::UpdateQueue::new(&send);
+        let mut text_renderer = TextRenderer::new(&device, config.format, sample_count);
+        text_renderer.set_scale_factor(window.scale_factor() as f32);
         (
             update_queue,
             Self {
-                frame_renderer: FrameRenderer::new(&device, &config),
-                grid_renderer: GridRenderer::new(&device, &config),
+                frame_renderer: FrameRenderer::new(&device, view_format, sample_count),
+                text_renderer,
+                image_renderer: ImageRenderer::new(&device, config.format, sample_count),
+                layout: Layout::new(),
+                overlay: DebugOverlay::new(),
                 size: size.cast(),
                 vertex_buffer,
                 index_render_target,
                 surface,
                 window,
                 msg_recv: recv,
-                grid_to_frame_map: vec![],
                 base_handle: window_handle,
                 config,
                 device,
                 queue,
                 msg_send: send,
                 proxy,
+                redraw_mode: RedrawMode::default(),
+                animations: 0,
+                redraw_requested: false,
             },
         )
     }
@@ -280,39 +450,26 @@ impl<'a> RenderManager<'a> {
         self.base_handle
     }
 
-    pub fn render(&'a self) -> Result<(), SurfaceError> {
+    pub fn render(&mut self) -> Result<(), SurfaceError> {
         let output = self.surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            self.frame_renderer.render(&mut render_pass);
-        }
+        self.frame_renderer.render(
+            &self.device,
+            &mut encoder,
+            &output.texture,
+            &self.vertex_buffer,
+            &[&self.image_renderer, &self.text_renderer],
+            wgpu::Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+        );
         {
             let index_view = self
                 .index_render_target
@@ -345,9 +502,20 @@ impl<'a> RenderManager<'a> {
         Ok(())
     }
     fn prepare(&mut self) {
-        self.grid_renderer
-            .prepare(&mut self.frame_renderer, &self.queue);
-        self.frame_renderer.prepare(&self.queue);
+        for e in self.layout.solve() {
+            if let Err(e) = self.proxy.send_event(RenderEvent::LayoutError(e)) {
+                warn!("{e}");
+            }
+        }
+        self.frame_renderer
+            .prepare(&self.device, &mut self.layout.frames, &self.queue);
+        self.image_renderer
+            .prepare(&mut self.layout.images, &self.layout.frames, &self.device, &self.queue);
+        self.text_renderer
+            .prepare(&self.layout.text, &self.layout.frames, &self.device, &self.queue);
+        self.overlay.prepare(&self.layout);
+        self.frame_renderer
+            .prepare_overlay(&self.device, self.overlay.quads(), &self.queue);
     }
     fn resize(&mut self, new_size: winit::dpi::LogicalSize<u32>, scale_factor: f64) {
         let size_pixels = new_size.to_physical(scale_factor);
@@ -358,87 +526,89 @@ impl<'a> RenderManager<'a> {
             self.surface.configure(&self.device, &self.config);
         }
     }
+    /// asks the window for one redraw, changes arriving before it is prepared share it
+    fn damage(&mut self) {
+        if !self.redraw_requested {
+            self.redraw_requested = true;
+            self.window.request_redraw();
+        }
+    }
     pub fn run_forever(mut self) {
         loop {
             let msg = self.msg_recv.recv().expect("update message recv err");
             if let UpdateMessage::Exit = msg {}
             match msg {
                 UpdateMessage::Draw => {
-                    let rr = self.render();
-                    let Err(e) = rr else {
-                        self.window.request_redraw();
-                        continue;
-                    };
-                    match e {
-                        SurfaceError::Lost => self.resize(self.size, self.window.scale_factor()),
-                        _ => warn!("{e}"),
-                    }
-                }
-                UpdateMessage::Prepare => self.prepare(),
-                UpdateMessage::ModifyFrame(h, f) => {
-                    let FrameMessage { size, color, .. } = f;
-                    if let Some(size) = size {
-                        self.frame_renderer.update(h.index(), &size);
+                    match self.render() {
+                        Ok(()) => (),
+                        Err(SurfaceError::Lost) => {
+                            self.resize(self.size, self.window.scale_factor());
+                            self.damage();
+                        }
+                        Err(e) => warn!("{e}"),
                     }
-                    if let Some(color) = color {
-                        self.frame_renderer.update_color(h.index(), color);
+                    if self.redraw_mode == RedrawMode::Continuous || self.animations > 0 {
+                        self.damage();
                     }
                 }
-                UpdateMessage::NewFrame(grid, x, y, f, frame_handle) => {
-                    let FrameMessage {
-                        size,
-                        color,
-                        margin,
-                    } = f;
-                    let size = size.unwrap_or(BBox::zeroed());
-                    let color = color.unwrap_or([0; 4]);
-                    let margin = margin.unwrap_or(MarginBox::zeroed());
-                    self.frame_renderer.add(crate::FrameData {
-                        data: size,
-                        margin: margin,
-                        color: color,
-                        camera_index: self.grid_to_frame_map[grid.index()].index() as u32,
-                    });
-                    self.grid_renderer
-                        .add_frame(&mut self.frame_renderer, grid, frame_handle, x, y)
-                }
-                UpdateMessage::NewFloatingFrame(f) => {
-                    let FrameMessage {
-                        size,
-                        color,
-                        margin,
-                    } = f;
-                    let size = size.unwrap_or(BBox::zeroed());
-                    let color = color.unwrap_or([0; 4]);
-                    let margin = margin.unwrap_or(MarginBox::zeroed());
-                    self.frame_renderer.add(crate::FrameData {
-                        data: size,
-                        margin: margin,
-                        color: color,
-                        camera_index: 0,
-                    });
-                }
-                UpdateMessage::ModifyGrid(_grid, _g) => (),
-                UpdateMessage::NewGrid(_grid_index, grid_builder) => {
-                    self.grid_to_frame_map.push(grid_builder.parent());
-                    self.grid_renderer.add(grid_builder.build());
+                UpdateMessage::Prepare => {
+                    // changes from here on need another redraw
+                    self.redraw_requested = false;
+                    self.prepare();
                 }
                 UpdateMessage::ResizeWindow(logical, scale_factor) => {
-                    self.frame_renderer.update(0, &Rect {
-                        x: 0, y: 0, w: logical.width, h: logical.height
-                    }.into());
+                    self.layout.resize(logical.width, logical.height);
+                    self.text_renderer.set_scale_factor(scale_factor as f32);
                     self.resize(logical, scale_factor);
+                    self.damage();
+                }
+                UpdateMessage::DebugOverlay(enabled) => {
+                    self.overlay.set(enabled);
+                    self.damage();
+                }
+                UpdateMessage::RedrawMode(mode) => {
+                    self.redraw_mode = mode;
+                    self.damage();
+                }
+                UpdateMessage::Animating(true) => {
+                    self.animations += 1;
+                    self.damage();
+                }
+                UpdateMessage::Animating(false) => {
+                    self.animations = self.animations.saturating_sub(1);
+                }
+                UpdateMessage::DumpLayout(send) => {
+                    if let Err(e) = send.send(self.layout.snapshot()) {
+                        warn!("{e}");
+                    }
                 }
                 UpdateMessage::Exit => {
-                    self.proxy.send_event(()).unwrap();
+                    self.proxy.send_event(RenderEvent::Exit).unwrap();
                     break;
                 }
+                msg => match self.layout.apply(msg) {
+                    Ok(true) => self.damage(),
+                    Ok(false) => (),
+                    Err(e) => {
+                        // the frame a failed message created is still drawn
+                        self.damage();
+                        if let Err(e) = self.proxy.send_event(RenderEvent::LayoutError(e)) {
+                            warn!("{e}");
+                        }
+                    }
+                },
             }
         }
     }
 }
 
 pub fn run<App: State<Param = ()>>() {
+    run_multisampled::<App>(1)
+}
+
+/// like `run` with `sample_count` samples per pixel to smooth the edges of frames,
+/// 1 turns msaa off
+pub fn run_multisampled<App: State<Param = ()>>(sample_count: u32) {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
             std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -467,28 +637,32 @@ pub fn run<App: State<Param = ()>>() {
             .expect("Couldn't append canvas to document body.");
     }
 
-    let event_loop = EventLoop::new().unwrap();
+    let event_loop = EventLoopBuilder::with_user_event().build().unwrap();
     let proxy = event_loop.create_proxy();
     let window = WindowBuilder::new().build(&event_loop).unwrap();
     let (send, recv) = mpsc::channel();
 
     thread::scope(|s| {
-        let (queue, renderer) =
-            pollster::block_on(RenderManager::new(&window, send.clone(), recv, proxy));
+        let (queue, renderer) = pollster::block_on(RenderManager::new(
+            &window,
+            send.clone(),
+            recv,
+            proxy,
+            sample_count,
+        ));
         let mut builder = ComponentBuilder::new(send.clone(), queue.clone());
         s.spawn(move || {
             renderer.run_forever();
         });
-        let _app: Component<App> = builder.send_app(
-            Rect {
-                x: 0,
-                y: 0,
-                w: 400,
-                h: 400,
-            }
-            .into(),
-        );
+        let _app: Component<App> = builder.send_app(Rect {
+            x: 0,
+            y: 0,
+            w: 400,
+            h: 400,
+        });
         let mut scale_factor = window.scale_factor();
+        let mut pointer = [0.0f32; 2];
+        let mut dragging = false;
         let exit_status =
             event_loop.run(move |event: Event<_>, target: &EventLoopWindowTarget<_>| {
                 match event {
@@ -509,6 +683,18 @@ pub fn run<App: State<Param = ()>>() {
                             } => {
                                 send.send(UpdateMessage::Exit).unwrap();
                             }
+                            WindowEvent::KeyboardInput {
+                                event:
+                                    KeyEvent {
+                                        state: ElementState::Pressed,
+                                        logical_key: Key::Named(NamedKey::F12),
+                                        repeat: false,
+                                        ..
+                                    },
+                                ..
+                            } => {
+                                send.send(UpdateMessage::DebugOverlay(None)).unwrap();
+                            }
                             WindowEvent::Resized(physical_size) => {
                                 let logical_size = physical_size.to_logical(scale_factor);
                                 queue.send(Update::System(update_queue::back::SystemUpdates::Resized(logical_size, scale_factor)));
@@ -521,7 +707,54 @@ pub fn run<App: State<Param = ()>>() {
 
                                 send.send(UpdateMessage::Draw).unwrap();
                             }
-                            WindowEvent::MouseInput { state, .. } => {
+                            WindowEvent::KeyboardInput {
+                                event:
+                                    KeyEvent {
+                                        state: ElementState::Pressed,
+                                        logical_key: Key::Named(key),
+                                        ..
+                                    },
+                                ..
+                            } => {
+                                let key = match key {
+                                    NamedKey::ArrowUp => ScrollKey::Up,
+                                    NamedKey::ArrowDown => ScrollKey::Down,
+                                    NamedKey::ArrowLeft => ScrollKey::Left,
+                                    NamedKey::ArrowRight => ScrollKey::Right,
+                                    NamedKey::PageUp => ScrollKey::PageUp,
+                                    NamedKey::PageDown => ScrollKey::PageDown,
+                                    NamedKey::Home => ScrollKey::Home,
+                                    NamedKey::End => ScrollKey::End,
+                                    _ => return,
+                                };
+                                send.send(UpdateMessage::ScrollInput(ScrollInput::Key(key))).unwrap();
+                            }
+                            WindowEvent::CursorMoved { position, .. } => {
+                                let position: LogicalPosition<f32> = position.to_logical(scale_factor);
+                                let [x, y] = pointer;
+                                if dragging {
+                                    send.send(UpdateMessage::ScrollInput(ScrollInput::Drag([position.x - x, position.y - y]))).unwrap();
+                                }
+                                pointer = [position.x, position.y];
+                                send.send(UpdateMessage::Pointer(position.x, position.y)).unwrap();
+                            }
+                            WindowEvent::MouseWheel { delta, .. } => {
+                                let delta = match delta {
+                                    MouseScrollDelta::LineDelta(x, y) => [x * LINE_PIXELS, y * LINE_PIXELS],
+                                    MouseScrollDelta::PixelDelta(p) => {
+                                        let p: LogicalPosition<f32> = p.to_logical(scale_factor);
+                                        [p.x, p.y]
+                                    }
+                                };
+                                send.send(UpdateMessage::ScrollInput(ScrollInput::Wheel(delta))).unwrap();
+                            }
+                            WindowEvent::MouseInput { state, button, .. } => {
+                                if *button == winit::event::MouseButton::Left {
+                                    dragging = state.is_pressed();
+                                    if !dragging {
+                                        send.send(UpdateMessage::ScrollInput(ScrollInput::DragEnd)).unwrap();
+                                    }
+                                }
                                 builder.emit_mouse(MouseEvent::Click(MouseButton::Left(
                                     match state {
                                         ElementState::Pressed => ButtonState::Pressed,
@@ -532,7 +765,10 @@ pub fn run<App: State<Param = ()>>() {
                             _ => {}
                         }
                     }
-                    Event::UserEvent(()) => {
+                    Event::UserEvent(RenderEvent::LayoutError(e)) => {
+                        builder.emit_error(e);
+                    }
+                    Event::UserEvent(RenderEvent::Exit) => {
                         target.exit();
                     }
                     _ => {}
@@ -1,12 +1,47 @@
-use crate::grid::{GridBuilder, GridHandle, XName, YName};
-use crate::manager::{BBox, MarginBox};
+use crate::color::Color;
+use crate::error::LayoutError;
+use crate::floating::Anchor;
+use crate::grid::{Direction, GridBuilder, GridHandle, XName, YName};
+use crate::layout::LayoutSnapshot;
+use crate::frame::{AspectRatio, Border, Gradient, Layer, Shadow};
+use crate::manager::{BBox, Borders, Corners, RedrawMode, Rect};
+use crate::units::UserUnits;
+use crate::scroll::{ScrollCommand, ScrollExtents, ScrollInput};
+use crate::images::{ImageHandle, ImageMessage};
+use crate::text::{FontHandle, TextMessage};
 use crate::FrameHandle;
+use std::sync::{mpsc, Arc, Mutex};
 
 #[derive(Clone, Default, Debug)]
 pub struct FrameMessage {
     pub size: Option<BBox>,
-    pub color: Option<[u8; 4]>,
-    pub margin: Option<MarginBox>,
+    pub color: Option<Color>,
+    /// resolved against the frame's own bounds whenever they change
+    pub margin: Option<Borders<UserUnits>>,
+    /// clip the frame's children to its visible bounds
+    pub clip: Option<bool>,
+    /// keeps the frame's bounds at this ratio inside of its cell, a `ratio` of 0 stretches it
+    /// again, the root frame ignores it since it always fills the window
+    pub aspect_ratio: Option<AspectRatio>,
+    /// rounds the corners of the visible bounds, they can't be clicked outside of the curve
+    pub radius: Option<Corners<UserUnits>>,
+    pub border: Option<Borders<Border>>,
+    /// replaces `color`, a gradient without stops goes back to it
+    pub gradient: Option<Gradient>,
+    /// drawn right before the frame, a transparent color removes it
+    pub shadow: Option<Shadow>,
+    /// blurs what is already drawn behind the frame by this radius, 0 removes it,
+    /// the frame's color is drawn on top so it needs some transparency
+    pub backdrop_blur: Option<UserUnits>,
+    /// draws the frame above its siblings with a lower z index, equal ones keep their creation order
+    pub z_index: Option<i32>,
+    /// every frame of a higher layer is drawn and hit tested on top, no matter where it was created
+    pub layer: Option<Layer>,
+    /// multiplies the alpha of the frame with its text and image, from 0 to 1
+    pub opacity: Option<f32>,
+    /// applies `opacity` to the frame and its descendants in the same layer as if they were drawn as one,
+    /// overlapping children don't show through each other
+    pub opacity_group: Option<bool>,
 }
 #[derive(Clone, Default, Debug)]
 pub struct GridMessage {}
@@ -22,10 +57,38 @@ pub enum UpdateMessage {
         FrameMessage,
         FrameHandle,
     ),
-    NewFloatingFrame(FrameMessage),
+    NewFloatingFrame(Rect<UserUnits>, FrameMessage),
+    NewAnchoredFrame(FrameHandle, Anchor, Rect<UserUnits>, FrameMessage),
     ModifyGrid(GridHandle, GridMessage),
     NewGrid(GridHandle, GridBuilder),
+    NewScroll(GridHandle, Arc<Mutex<ScrollExtents>>),
+    Scroll(GridHandle, ScrollCommand),
+    ScrollInput(ScrollInput),
+    Pointer(f32, f32),
+    NewFont(FontHandle, ab_glyph::FontArc),
+    ModifyText(FrameHandle, TextMessage),
+    NewImage(ImageHandle, image::RgbaImage),
+    ModifyImage(FrameHandle, ImageMessage),
+    RemoveImage(ImageHandle),
+    /// replies with the layout as of the last prepare
+    DumpLayout(mpsc::Sender<LayoutSnapshot>),
+    /// logical pixels in one `Rem`
+    RootUnit(f32),
+    /// direction of grids that don't set their own
+    Direction(Direction),
+    /// shows grid cells, frame bounds and frame indices, `None` toggles it
+    DebugOverlay(Option<bool>),
+    RedrawMode(RedrawMode),
+    /// `true` starts an animation and `false` ends one, the window redraws while any are running
+    Animating(bool),
     Prepare,
     Draw,
     Exit,
 }
+
+/// sent from the render thread back to the event loop
+#[derive(Debug)]
+pub enum RenderEvent {
+    LayoutError(LayoutError),
+    Exit,
+}
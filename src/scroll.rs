@@ -0,0 +1,388 @@
+use std::sync::{mpsc, Arc, Mutex};
+
+use log::warn;
+
+use crate::{
+    frame::{FrameHandle, FrameStore},
+    grid::{GridHandle, GridRenderer},
+    handle::HandleLike,
+    render_actor::UpdateMessage,
+    units::VUnit,
+};
+
+pub(crate) const LINE_PIXELS: f32 = 40.0;
+/// logical pixels the pointer has to move before a drag scrolls, so clicks don't scroll
+pub(crate) const DRAG_THRESHOLD: f32 = 6.0;
+
+#[derive(Clone, Copy, Debug)]
+pub enum ScrollKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+/// user input routed to the scroll container under the pointer,
+/// deltas are in logical pixels and move the content
+#[derive(Clone, Copy, Debug)]
+pub enum ScrollInput {
+    Wheel([f32; 2]),
+    /// pointer movement with the button held, it goes to the container the drag started on
+    Drag([f32; 2]),
+    /// the button was released
+    DragEnd,
+    Key(ScrollKey),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ScrollCommand {
+    To([f32; 2]),
+    By([f32; 2]),
+}
+
+/// last solved state of a scroll container in logical pixels,
+/// right to left content starts at the right edge and scrolls to negative x offsets
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ScrollExtents {
+    pub offset: [f32; 2],
+    pub content: [f32; 2],
+    pub viewport: [f32; 2],
+}
+
+/// handle to a scroll container, offsets are clamped to the content on the next layout
+#[derive(Clone)]
+pub struct Scroll {
+    grid: GridHandle,
+    extents: Arc<Mutex<ScrollExtents>>,
+    sender: mpsc::Sender<UpdateMessage>,
+}
+
+impl Scroll {
+    pub(crate) fn new(
+        grid: GridHandle,
+        extents: Arc<Mutex<ScrollExtents>>,
+        sender: mpsc::Sender<UpdateMessage>,
+    ) -> Self {
+        Self {
+            grid,
+            extents,
+            sender,
+        }
+    }
+    fn send(&self, command: ScrollCommand) {
+        if let Err(e) = self.sender.send(UpdateMessage::Scroll(self.grid, command)) {
+            warn!("{e}");
+        }
+    }
+    pub fn scroll_to(&self, x: f32, y: f32) {
+        self.send(ScrollCommand::To([x, y]))
+    }
+    pub fn scroll_by(&self, dx: f32, dy: f32) {
+        self.send(ScrollCommand::By([dx, dy]))
+    }
+    pub fn extents(&self) -> ScrollExtents {
+        *self.extents.lock().unwrap()
+    }
+    pub fn grid(&self) -> GridHandle {
+        self.grid
+    }
+}
+
+struct ScrollContainer {
+    grid: GridHandle,
+    frame: FrameHandle,
+    offset: [f32; 2],
+    extents: Arc<Mutex<ScrollExtents>>,
+    /// direction of the grid as of the last layout
+    rtl: bool,
+}
+
+impl ScrollContainer {
+    /// the offset as the next layout clamps it, against the last solved extents
+    fn clamped(&self) -> [f32; 2] {
+        clamp(self.offset, &self.extents.lock().unwrap(), self.rtl)
+    }
+    fn scroll_by(&mut self, [dx, dy]: [f32; 2]) {
+        self.offset = [self.offset[0] + dx, self.offset[1] + dy];
+    }
+    /// content can only be dragged along an axis it overflows
+    fn scrollable(&self) -> bool {
+        let e = self.extents.lock().unwrap();
+        (0..2).any(|i| e.content[i] > e.viewport[i])
+    }
+    fn key(&mut self, key: ScrollKey) {
+        let ScrollExtents {
+            viewport, content, ..
+        } = *self.extents.lock().unwrap();
+        let page = (viewport[1] - LINE_PIXELS).max(LINE_PIXELS);
+        match key {
+            ScrollKey::Up => self.scroll_by([0.0, -LINE_PIXELS]),
+            ScrollKey::Down => self.scroll_by([0.0, LINE_PIXELS]),
+            ScrollKey::Left => self.scroll_by([-LINE_PIXELS, 0.0]),
+            ScrollKey::Right => self.scroll_by([LINE_PIXELS, 0.0]),
+            ScrollKey::PageUp => self.scroll_by([0.0, -page]),
+            ScrollKey::PageDown => self.scroll_by([0.0, page]),
+            ScrollKey::Home => self.offset[1] = 0.0,
+            ScrollKey::End => self.offset[1] = content[1],
+        }
+    }
+}
+
+/// right to left grids overflow to the left of their parent, so their x offset is negative
+fn clamp(offset: [f32; 2], e: &ScrollExtents, rtl: bool) -> [f32; 2] {
+    [0, 1].map(|i| {
+        let overflow = (e.content[i] - e.viewport[i]).max(0.0);
+        match i == 0 && rtl {
+            true => offset[i].max(-overflow).min(0.0),
+            false => offset[i].min(overflow).max(0.0),
+        }
+    })
+}
+
+/// a drag in progress, `moved` adds up its deltas until they pass `DRAG_THRESHOLD`
+#[derive(Clone, Copy)]
+struct DragState {
+    container: Option<usize>,
+    moved: [f32; 2],
+    scrolling: bool,
+}
+
+#[derive(Default)]
+pub struct ScrollRenderer {
+    data: Vec<ScrollContainer>,
+    pointer: [f32; 2],
+    hovered: Option<usize>,
+    drag: Option<DragState>,
+}
+
+impl ScrollRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add(
+        &mut self,
+        grid: GridHandle,
+        frame: FrameHandle,
+        extents: Arc<Mutex<ScrollExtents>>,
+    ) {
+        self.data.push(ScrollContainer {
+            grid,
+            frame,
+            offset: [0.0; 2],
+            extents,
+            rtl: false,
+        });
+    }
+    /// returns whether the container moved
+    pub fn command(&mut self, grid: GridHandle, command: ScrollCommand) -> bool {
+        let Some(c) = self
+            .data
+            .iter_mut()
+            .find(|c| c.grid.index() == grid.index())
+        else {
+            warn!("grid {} is not a scroll container", grid.index());
+            return false;
+        };
+        let old = c.clamped();
+        match command {
+            ScrollCommand::To(offset) => c.offset = offset,
+            ScrollCommand::By(delta) => c.scroll_by(delta),
+        }
+        c.clamped() != old
+    }
+    fn offsets(&self) -> Vec<[f32; 2]> {
+        self.data.iter().map(ScrollContainer::clamped).collect()
+    }
+    /// innermost scroll container under the pointer, containers are created after their parents
+    fn hit(&self, frames: &FrameStore) -> Option<usize> {
+        let [px, py] = self.pointer;
+        let (px, py) = (VUnit::from(px), VUnit::from(py));
+        // the topmost container, nested ones are drawn after their parents
+        (0..self.data.len())
+            .filter(|&i| {
+                let b = frames.screen_bounds(self.data[i].frame.index());
+                px >= b.x && py >= b.y && px < b.x + b.w && py < b.y + b.h
+            })
+            .max_by_key(|&i| frames.draw_position(self.data[i].frame.index()))
+    }
+    pub fn pointer(&mut self, frames: &FrameStore, x: f32, y: f32) {
+        self.pointer = [x, y];
+        self.hovered = self.hit(frames);
+    }
+    /// returns whether a container moved
+    pub fn input(&mut self, frames: &FrameStore, input: ScrollInput) -> bool {
+        let old = self.offsets();
+        match input {
+            ScrollInput::Wheel([dx, dy]) => {
+                if let Some(i) = self.hit(frames) {
+                    self.data[i].scroll_by([-dx, -dy]);
+                }
+            }
+            ScrollInput::Drag(delta) => self.drag(frames, delta),
+            ScrollInput::DragEnd => self.drag = None,
+            ScrollInput::Key(key) => {
+                if let Some(i) = self.hovered {
+                    self.data[i].key(key);
+                }
+            }
+        }
+        self.offsets() != old
+    }
+    /// the first delta of a drag picks the scrollable container under the pointer
+    fn drag(&mut self, frames: &FrameStore, [dx, dy]: [f32; 2]) {
+        let hit = self.hit(frames).filter(|&i| self.data[i].scrollable());
+        let drag = self.drag.get_or_insert(DragState {
+            container: hit,
+            moved: [0.0; 2],
+            scrolling: false,
+        });
+        let Some(i) = drag.container else {
+            return;
+        };
+        if drag.scrolling {
+            self.data[i].scroll_by([-dx, -dy]);
+            return;
+        }
+        drag.moved = [drag.moved[0] + dx, drag.moved[1] + dy];
+        let [mx, my] = drag.moved;
+        if mx.hypot(my) >= DRAG_THRESHOLD {
+            drag.scrolling = true;
+            self.data[i].scroll_by([-mx, -my]);
+        }
+    }
+    /// must run after the grids are solved so the offsets are clamped to the new content
+    pub fn prepare(&mut self, grids: &GridRenderer, frames: &mut FrameStore) {
+        for c in &mut self.data {
+            let parent = frames.get(c.frame.index()).data;
+            let viewport = [parent.w.pix(), parent.h.pix()];
+            let content = grids.content_size(c.grid).map(|v| v.pix());
+            c.rtl = grids.get(c.grid).is_rtl(frames);
+            let mut extents = ScrollExtents {
+                offset: c.offset,
+                content,
+                viewport,
+            };
+            c.offset = clamp(c.offset, &extents, c.rtl);
+            extents.offset = c.offset;
+            frames.set_scroll(c.frame.index(), c.offset.map(VUnit::from));
+            *c.extents.lock().unwrap() = extents;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Builder, State, UpdateQueue},
+        grid::Direction,
+        layout::Layout,
+    };
+
+    /// 300 by 200 pixels of content scrolling in the whole window
+    struct Long;
+    impl State for Long {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.widths().template("300px").unwrap().build();
+            g.heights().template("50px 50px 50px 50px").unwrap().build();
+            let (g, _scroll) = builder.scroll_grid(g);
+            for _ in 0..4 {
+                builder.frame::<()>((), g, None, None);
+            }
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    fn scrolled(layout: &mut Layout, msg: UpdateMessage) -> (bool, [f32; 2]) {
+        let moved = layout.apply(msg).unwrap();
+        layout.solve();
+        (moved, layout.scroll.data[0].extents.lock().unwrap().offset)
+    }
+
+    #[test]
+    fn wheel_and_keys_move_the_offset() {
+        let mut layout = Layout::solved::<Long>(100, 100);
+        layout.apply(UpdateMessage::Pointer(10.0, 10.0)).unwrap();
+        let wheel = UpdateMessage::ScrollInput(ScrollInput::Wheel([0.0, -30.0]));
+        assert_eq!(scrolled(&mut layout, wheel), (true, [0.0, 30.0]));
+        let key = |key| UpdateMessage::ScrollInput(ScrollInput::Key(key));
+        assert_eq!(scrolled(&mut layout, key(ScrollKey::Down)), (true, [0.0, 70.0]));
+        // a page is the viewport less a line, the end clamps it
+        assert_eq!(scrolled(&mut layout, key(ScrollKey::PageDown)), (true, [0.0, 100.0]));
+        assert_eq!(scrolled(&mut layout, key(ScrollKey::Down)), (false, [0.0, 100.0]));
+        assert_eq!(scrolled(&mut layout, key(ScrollKey::Right)), (true, [40.0, 100.0]));
+        assert_eq!(scrolled(&mut layout, key(ScrollKey::Home)), (true, [40.0, 0.0]));
+    }
+
+    #[test]
+    fn small_drags_dont_scroll() {
+        let mut layout = Layout::solved::<Long>(100, 100);
+        layout.apply(UpdateMessage::Pointer(10.0, 10.0)).unwrap();
+        let drag = |delta| UpdateMessage::ScrollInput(ScrollInput::Drag(delta));
+        assert_eq!(scrolled(&mut layout, drag([-2.0, -2.0])), (false, [0.0, 0.0]));
+        assert_eq!(scrolled(&mut layout, drag([-2.0, -2.0])), (false, [0.0, 0.0]));
+        // past the threshold the whole drag scrolls at once
+        assert_eq!(scrolled(&mut layout, drag([-2.0, 0.0])), (true, [6.0, 4.0]));
+        assert_eq!(scrolled(&mut layout, drag([0.0, -10.0])), (true, [6.0, 14.0]));
+        let end = UpdateMessage::ScrollInput(ScrollInput::DragEnd);
+        assert_eq!(scrolled(&mut layout, end), (false, [6.0, 14.0]));
+        assert_eq!(scrolled(&mut layout, drag([-2.0, -2.0])), (false, [6.0, 14.0]));
+    }
+
+    #[test]
+    fn extents_are_clamped_after_prepare() {
+        let mut layout = Layout::solved::<Long>(100, 100);
+        let c = &layout.scroll.data[0];
+        let scroll = Scroll::new(c.grid, c.extents.clone(), mpsc::channel().0);
+        let to = UpdateMessage::Scroll(scroll.grid(), ScrollCommand::To([500.0, -20.0]));
+        layout.apply(to).unwrap();
+        layout.solve();
+        let ScrollExtents {
+            offset,
+            content,
+            viewport,
+        } = scroll.extents();
+        assert_eq!([offset, content, viewport], [[200.0, 0.0], [300.0, 200.0], [100.0; 2]]);
+    }
+
+    /// `Long` mirrored, its content overflows to the left of the window
+    struct RightToLeft;
+    impl State for RightToLeft {
+        type Msg = ();
+        type Param = ();
+        fn init<P: State>(builder: &mut Builder<P>, _: &()) -> Self {
+            let mut g = builder.grid_builder();
+            g.direction(Direction::Rtl);
+            g.widths().template("300px").unwrap().build();
+            g.heights().template("50px 50px 50px 50px").unwrap().build();
+            let (g, _scroll) = builder.scroll_grid(g);
+            builder.frame::<()>((), g, None, None);
+            Self
+        }
+        fn update(&mut self, _: (), _: &UpdateQueue) {}
+    }
+
+    #[test]
+    fn right_to_left_scrolls_towards_the_left() {
+        let mut layout = Layout::solved::<RightToLeft>(100, 100);
+        let grid = layout.scroll.data[0].grid;
+        let to = |x| UpdateMessage::Scroll(grid, ScrollCommand::To([x, 0.0]));
+        // the start is the right edge, the content can't scroll further right
+        assert_eq!(scrolled(&mut layout, to(50.0)), (false, [0.0, 0.0]));
+        assert_eq!(layout.frames.screen_bounds(1).x.pix(), -200.0);
+        layout.apply(UpdateMessage::Pointer(10.0, 10.0)).unwrap();
+        let wheel = UpdateMessage::ScrollInput(ScrollInput::Wheel([30.0, 0.0]));
+        assert_eq!(scrolled(&mut layout, wheel), (true, [-30.0, 0.0]));
+        assert_eq!(scrolled(&mut layout, to(-500.0)), (true, [-200.0, 0.0]));
+        // the left end of the content reaches the window's left edge
+        assert_eq!(layout.frames.screen_bounds(1).x.pix(), 0.0);
+    }
+}
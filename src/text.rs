@@ -0,0 +1,6 @@
+mod atlas;
+mod renderer;
+mod store;
+
+pub use renderer::TextRenderer;
+pub use store::{FontHandle, TextMessage, TextStore};
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScale};
+
+/// a glyph rasterized at one size, `bounds` is relative to its origin on the baseline
+#[derive(Clone, Copy, Debug)]
+pub struct AtlasGlyph {
+    pub uv: [f32; 4],
+    pub bounds: [f32; 4],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: usize,
+    glyph: GlyphId,
+    /// bits of the physical pixel scale
+    scale: u32,
+}
+
+/// the atlas has no space left for a glyph
+#[derive(Debug)]
+pub struct AtlasFull;
+
+/// single channel coverage of every glyph drawn, packed into rows
+pub struct GlyphAtlas {
+    texture: wgpu::Texture,
+    size: u32,
+    /// x and y of the next glyph and the height of the current row
+    cursor: [u32; 3],
+    glyphs: HashMap<GlyphKey, Option<AtlasGlyph>>,
+}
+
+impl GlyphAtlas {
+    /// space left around every glyph so filtering doesn't bleed into its neighbours
+    const PADDING: u32 = 1;
+
+    pub fn new(device: &wgpu::Device, size: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("glyph atlas"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        Self {
+            texture,
+            size,
+            cursor: [0; 3],
+            glyphs: HashMap::new(),
+        }
+    }
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+    /// forgets every glyph, their space is reused
+    pub fn clear(&mut self) {
+        self.cursor = [0; 3];
+        self.glyphs.clear();
+    }
+    /// `None` for glyphs without an outline like spaces
+    pub fn get(
+        &mut self,
+        queue: &wgpu::Queue,
+        font_index: usize,
+        font: &FontArc,
+        glyph: GlyphId,
+        scale: f32,
+    ) -> Result<Option<AtlasGlyph>, AtlasFull> {
+        let key = GlyphKey {
+            font: font_index,
+            glyph,
+            scale: scale.to_bits(),
+        };
+        if let Some(g) = self.glyphs.get(&key) {
+            return Ok(*g);
+        }
+        let Some(outline) = font.outline_glyph(glyph.with_scale(PxScale::from(scale))) else {
+            self.glyphs.insert(key, None);
+            return Ok(None);
+        };
+        let bounds = outline.px_bounds();
+        let (w, h) = (bounds.width() as u32, bounds.height() as u32);
+        let [x, y] = self.allocate(w, h)?;
+        let mut coverage = vec![0u8; (w * h) as usize];
+        outline.draw(|gx, gy, c| {
+            if gx < w && gy < h {
+                coverage[(gy * w + gx) as usize] = (c.clamp(0.0, 1.0) * 255.0) as u8;
+            }
+        });
+        if w > 0 && h > 0 {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &coverage,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(w),
+                    rows_per_image: Some(h),
+                },
+                wgpu::Extent3d {
+                    width: w,
+                    height: h,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        let size = self.size as f32;
+        let g = AtlasGlyph {
+            uv: [
+                x as f32 / size,
+                y as f32 / size,
+                w as f32 / size,
+                h as f32 / size,
+            ],
+            bounds: [bounds.min.x, bounds.min.y, w as f32, h as f32],
+        };
+        self.glyphs.insert(key, Some(g));
+        Ok(Some(g))
+    }
+    fn allocate(&mut self, w: u32, h: u32) -> Result<[u32; 2], AtlasFull> {
+        let [mut x, mut y, mut row] = self.cursor;
+        if x + w + Self::PADDING > self.size {
+            x = 0;
+            y += row;
+            row = 0;
+        }
+        if x + w + Self::PADDING > self.size || y + h + Self::PADDING > self.size {
+            return Err(AtlasFull);
+        }
+        self.cursor = [x + w + Self::PADDING, y, row.max(h + Self::PADDING)];
+        Ok([x, y])
+    }
+}
@@ -0,0 +1,504 @@
+use std::{mem, ops::Range};
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, PxScaleFont, ScaleFont};
+use bytemuck::{Pod, Zeroable};
+use log::warn;
+use wgpu::{include_wgsl, Device, MultisampleState, RenderPass, RenderPipeline};
+
+use crate::{
+    color::Color,
+    floating::Align,
+    frame::{grown, ContentSpan, FrameContent, FrameStore},
+    manager::{BBox, Vertex},
+    units::UnitContext,
+};
+
+use super::{
+    atlas::{AtlasFull, GlyphAtlas},
+    store::{Text, TextStore},
+};
+
+#[derive(Pod, Zeroable, Clone, Copy, Debug)]
+#[repr(C)]
+struct GlyphData {
+    /// logical pixels on screen
+    rect: [f32; 4],
+    uv: [f32; 4],
+    clip: [f32; 4],
+    color: Color,
+}
+
+impl GlyphData {
+    const fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as u64,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 1,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 16,
+                    shader_location: 2,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 32,
+                    shader_location: 3,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Unorm8x4,
+                    offset: 48,
+                    shader_location: 4,
+                },
+            ],
+        }
+    }
+}
+
+/// draws the text of every frame from a glyph atlas, one quad per glyph
+pub struct TextRenderer {
+    pipeline: RenderPipeline,
+    viewport_buffer: wgpu::Buffer,
+    viewport_bg: wgpu::BindGroup,
+    atlas: GlyphAtlas,
+    atlas_bg: wgpu::BindGroup,
+    glyph_buffer: wgpu::Buffer,
+    /// glyphs drawn right before the frame at each index
+    breaks: Vec<(u32, Range<u32>)>,
+    scale_factor: f32,
+}
+
+impl TextRenderer {
+    const ATLAS_SIZE: u32 = 1024;
+    /// starting number of glyphs, the instance buffer grows past it
+    const GLYPH_CAPACITY: usize = 50000;
+
+    /// draws into the srgb view of `format` a `FrameRenderer` renders through
+    pub fn new(device: &Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let shader = include_wgsl!("shader.wgsl");
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("text shader"),
+            source: shader.source,
+        });
+        let viewport_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("text viewport buffer"),
+            size: mem::size_of::<[f32; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let viewport_bg_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("text viewport layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let viewport_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text viewport bg"),
+            layout: &viewport_bg_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: viewport_buffer.as_entire_binding(),
+            }],
+        });
+        let atlas = GlyphAtlas::new(device, Self::ATLAS_SIZE);
+        let atlas_bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("glyph atlas layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("glyph atlas sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let atlas_view = atlas
+            .texture()
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let atlas_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("glyph atlas bg"),
+            layout: &atlas_bg_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("text pipeline layout"),
+            bind_group_layouts: &[&viewport_bg_layout, &atlas_bg_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("text pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc(), GlyphData::desc()],
+            },
+            primitive: Vertex::state(),
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: format.add_srgb_suffix(),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        let glyph_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("glyph instance buffer"),
+            size: (mem::size_of::<GlyphData>() * Self::GLYPH_CAPACITY) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            pipeline,
+            viewport_buffer,
+            viewport_bg,
+            atlas,
+            atlas_bg,
+            glyph_buffer,
+            breaks: vec![],
+            scale_factor: 1.0,
+        }
+    }
+    /// glyphs are rasterized at physical pixels, changing it rasterizes them again
+    pub fn set_scale_factor(&mut self, scale_factor: f32) {
+        if scale_factor != self.scale_factor {
+            self.scale_factor = scale_factor;
+            self.atlas.clear();
+        }
+    }
+    /// lays out the text of every frame against the frames' bounds as of their last `solve_cameras`
+    pub fn prepare(
+        &mut self,
+        text: &TextStore,
+        frames: &FrameStore,
+        device: &Device,
+        queue: &wgpu::Queue,
+    ) {
+        self.breaks.clear();
+        if frames.is_empty() {
+            return;
+        }
+        let window = frames.get(0).data;
+        queue.write_buffer(
+            &self.viewport_buffer,
+            0,
+            bytemuck::cast_slice(&[window.w.pix(), window.h.pix(), 0.0, 0.0]),
+        );
+        let (glyphs, owners) = match self.layout(text, frames, queue, true) {
+            Ok(res) => res,
+            Err(AtlasFull) => {
+                self.atlas.clear();
+                match self.layout(text, frames, queue, false) {
+                    Ok(res) => res,
+                    Err(AtlasFull) => unreachable!("partial layouts skip glyphs that don't fit"),
+                }
+            }
+        };
+        let bytes = mem::size_of_val(glyphs.as_slice()) as u64;
+        if let Some(buffer) = grown(device, &self.glyph_buffer, "glyph instance buffer", bytes) {
+            self.glyph_buffer = buffer;
+        }
+        queue.write_buffer(&self.glyph_buffer, 0, bytemuck::cast_slice(&glyphs));
+        self.breaks = frames.content_breaks(&owners);
+    }
+    /// `strict` gives up once the atlas is full, otherwise glyphs that don't fit are skipped
+    fn layout(
+        &mut self,
+        text: &TextStore,
+        frames: &FrameStore,
+        queue: &wgpu::Queue,
+        strict: bool,
+    ) -> Result<(Vec<GlyphData>, Vec<ContentSpan>), AtlasFull> {
+        let mut glyphs = vec![];
+        let mut owners = vec![];
+        let mut dropped = false;
+        let ctx = frames.unit_context();
+        for (position, &frame) in frames.draw_order().iter().enumerate() {
+            let frame = frame as usize;
+            let Some(t) = text.get(frame).filter(|t| !t.text.is_empty()) else {
+                continue;
+            };
+            let Some((font_index, font)) = text.font(t.font) else {
+                warn!("frame {frame} has text but no font is loaded");
+                continue;
+            };
+            let bounds = frames.screen_bounds(frame);
+            let area = frames.clipped_bounds(frame);
+            if area.w <= 0.into() || area.h <= 0.into() {
+                continue;
+            }
+            let start = glyphs.len() as u32;
+            let clip = [area.x.pix(), area.y.pix(), area.w.pix(), area.h.pix()];
+            let opacity = frames.get(frame).opacity[0];
+            let color = t.color.with_alpha((t.color.a as f32 * opacity).round() as u8);
+            let rtl = frames.is_rtl(frame);
+            for (id, rect) in self.place(font, t, &bounds, rtl, &ctx) {
+                let g = match self.atlas.get(queue, font_index, font, id, rect[2]) {
+                    Ok(Some(g)) => g,
+                    Ok(None) => continue,
+                    Err(e) if strict => return Err(e),
+                    Err(AtlasFull) => {
+                        dropped = true;
+                        continue;
+                    }
+                };
+                let sf = self.scale_factor;
+                let [x, y, w, h] = g.bounds;
+                glyphs.push(GlyphData {
+                    rect: [(rect[0] + x) / sf, (rect[1] + y) / sf, w / sf, h / sf],
+                    uv: g.uv,
+                    clip,
+                    color,
+                });
+            }
+            owners.push((position as u32, start..glyphs.len() as u32, area));
+        }
+        if dropped {
+            warn!("the glyph atlas is full, some text is not drawn");
+        }
+        Ok((glyphs, owners))
+    }
+    /// every glyph with its origin in physical pixels and the scale it is rasterized at,
+    /// `rtl` swaps the `Start` and `End` alignment
+    fn place(
+        &self,
+        font: &FontArc,
+        t: &Text,
+        bounds: &BBox,
+        rtl: bool,
+        ctx: &UnitContext,
+    ) -> Vec<(GlyphId, [f32; 3])> {
+        let sf = self.scale_factor;
+        // like css the size is the font's em, not the height of its glyphs
+        let em = font.units_per_em().unwrap_or(1.0);
+        let size = t.size.solve(bounds.h, ctx).pix();
+        let scale = size * sf * font.height_unscaled() / em;
+        if scale <= 0.0 {
+            return vec![];
+        }
+        let scaled = font.as_scaled(PxScale::from(scale));
+        let line_height = scaled.height() + scaled.line_gap();
+        let width = bounds.w.pix() * sf;
+        let lines = wrap(&scaled, &t.text, width);
+        let block = lines.len() as f32 * line_height - scaled.line_gap();
+        let top = bounds.y.pix() * sf + offset(t.vertical_align, bounds.h.pix() * sf, block);
+        let mut res = vec![];
+        let align = t.align.mirrored(rtl);
+        for (i, (line, line_width)) in lines.into_iter().enumerate() {
+            let left = bounds.x.pix() * sf + offset(align, width, line_width);
+            let baseline = (top + scaled.ascent() + i as f32 * line_height).round();
+            res.extend(
+                line.into_iter()
+                    .map(|(id, x)| (id, [(left + x).round(), baseline, scale])),
+            );
+        }
+        res
+    }
+}
+
+impl FrameContent for TextRenderer {
+    fn draw_breaks(&self) -> &[(u32, Range<u32>)] {
+        &self.breaks
+    }
+    fn draw<'a: 'rp, 'rp>(&'a self, render_pass: &mut RenderPass<'rp>, glyphs: Range<u32>) {
+        if glyphs.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.viewport_bg, &[]);
+        render_pass.set_bind_group(1, &self.atlas_bg, &[]);
+        render_pass.set_vertex_buffer(1, self.glyph_buffer.slice(..));
+        render_pass.draw(0..4, glyphs);
+    }
+}
+
+/// start of a span of `len` aligned inside of `space`
+fn offset(align: Align, space: f32, len: f32) -> f32 {
+    match align {
+        Align::Start => 0.0,
+        Align::Center => (space - len) / 2.0,
+        Align::End => space - len,
+    }
+}
+
+/// the metrics `wrap` needs from a scaled font
+trait Advances {
+    fn glyph_id(&self, c: char) -> GlyphId;
+    fn h_advance(&self, id: GlyphId) -> f32;
+    fn kern(&self, first: GlyphId, second: GlyphId) -> f32;
+}
+
+impl<F: Font> Advances for PxScaleFont<F> {
+    fn glyph_id(&self, c: char) -> GlyphId {
+        ScaleFont::glyph_id(self, c)
+    }
+    fn h_advance(&self, id: GlyphId) -> f32 {
+        ScaleFont::h_advance(self, id)
+    }
+    fn kern(&self, first: GlyphId, second: GlyphId) -> f32 {
+        ScaleFont::kern(self, first, second)
+    }
+}
+
+/// glyphs of every line with their x offset, and the width of the line without trailing spaces,
+/// lines are broken at `\n` and before words that would overflow `max_width`
+fn wrap(font: &impl Advances, text: &str, max_width: f32) -> Vec<(Vec<(GlyphId, f32)>, f32)> {
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut line = vec![];
+        let mut x = 0.0;
+        let mut width = 0.0f32;
+        for word in paragraph.split_inclusive(' ') {
+            let trimmed = word.trim_end_matches(' ').chars().count();
+            let mut glyphs = vec![];
+            let mut w = 0.0;
+            let mut end = 0.0;
+            let mut prev = None;
+            for (i, c) in word.chars().enumerate() {
+                let id = font.glyph_id(c);
+                if let Some(p) = prev {
+                    w += font.kern(p, id);
+                }
+                glyphs.push((id, w));
+                w += font.h_advance(id);
+                if i + 1 == trimmed {
+                    end = w;
+                }
+                prev = Some(id);
+            }
+            if !line.is_empty() && x + end > max_width {
+                lines.push((line, width));
+                line = vec![];
+                x = 0.0;
+                width = 0.0;
+            }
+            line.extend(glyphs.into_iter().map(|(id, gx)| (id, x + gx)));
+            width = width.max(x + end);
+            x += w;
+        }
+        lines.push((line, width));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// every glyph is 10 wide and a space 5, `AV` kerns by -2
+    struct Mono;
+    impl Advances for Mono {
+        fn glyph_id(&self, c: char) -> GlyphId {
+            GlyphId(c as u16)
+        }
+        fn h_advance(&self, id: GlyphId) -> f32 {
+            match id.0 {
+                0x20 => 5.0,
+                _ => 10.0,
+            }
+        }
+        fn kern(&self, first: GlyphId, second: GlyphId) -> f32 {
+            match (first.0 as u8, second.0 as u8) {
+                (b'A', b'V') => -2.0,
+                _ => 0.0,
+            }
+        }
+    }
+
+    /// every line as its text, first glyph offset and width
+    fn lines(text: &str, max_width: f32) -> Vec<(String, f32, f32)> {
+        wrap(&Mono, text, max_width)
+            .into_iter()
+            .map(|(glyphs, width)| {
+                let text = glyphs.iter().map(|(id, _)| id.0 as u8 as char).collect();
+                (text, glyphs.first().map_or(0.0, |g| g.1), width)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wrap_fits_a_line() {
+        assert_eq!(lines("ab cd", 100.0), [("ab cd".into(), 0.0, 45.0)]);
+        let glyphs: Vec<f32> = wrap(&Mono, "ab cd", 100.0)[0].0.iter().map(|g| g.1).collect();
+        assert_eq!(glyphs, [0.0, 10.0, 20.0, 25.0, 35.0]);
+    }
+
+    #[test]
+    fn wrap_breaks_before_overflowing_words() {
+        // trailing spaces don't count towards the width
+        assert_eq!(
+            lines("ab cd ef", 45.0),
+            [("ab cd ".into(), 0.0, 45.0), ("ef".into(), 0.0, 20.0)]
+        );
+        // a word longer than the line stays whole
+        assert_eq!(
+            lines("a abcdef", 30.0),
+            [("a ".into(), 0.0, 10.0), ("abcdef".into(), 0.0, 60.0)]
+        );
+    }
+
+    #[test]
+    fn wrap_keeps_newlines_and_kerning() {
+        assert_eq!(
+            lines("AV\n\nb", 100.0),
+            [("AV".into(), 0.0, 18.0), ("".into(), 0.0, 0.0), ("b".into(), 0.0, 10.0)]
+        );
+    }
+
+    #[test]
+    fn offset_aligns_in_space() {
+        assert_eq!(offset(Align::Start, 100.0, 40.0), 0.0);
+        assert_eq!(offset(Align::Center, 100.0, 40.0), 30.0);
+        assert_eq!(offset(Align::End, 100.0, 40.0), 60.0);
+        // overflowing lines keep their start for `Start` and grow to the left otherwise
+        assert_eq!(offset(Align::End, 100.0, 140.0), -40.0);
+        assert_eq!(offset(Align::Start.mirrored(true), 100.0, 40.0), 60.0);
+        assert_eq!(offset(Align::End.mirrored(true), 100.0, 40.0), 0.0);
+    }
+}
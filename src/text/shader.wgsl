@@ -0,0 +1,60 @@
+// Vertex shader
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    // logical pixels on screen
+    @location(1) rect: vec4<f32>,
+    // xywh in the atlas
+    @location(2) uv: vec4<f32>,
+    // screen space xywh the glyph is clipped to
+    @location(3) clip: vec4<f32>,
+    @location(4) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) screen: vec2<f32>,
+    @location(2) @interpolate(flat) clip: vec4<f32>,
+    @location(3) @interpolate(flat) color: vec4<f32>,
+};
+
+// width and height of the window in logical pixels
+@group(0) @binding(0)
+var<uniform> viewport: vec4<f32>;
+
+@group(1) @binding(0)
+var atlas: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+// colors arrive as srgb and are blended in linear space, the srgb target encodes them again
+fn srgb_to_linear(c: vec4<f32>) -> vec4<f32> {
+    let curve = pow((c.rgb + 0.055) / 1.055, vec3<f32>(2.4));
+    let rgb = select(curve, c.rgb / 12.92, c.rgb <= vec3<f32>(0.04045));
+    return vec4<f32>(rgb, c.a);
+}
+
+@vertex
+fn vs_main(v: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let corner = v.position / vec2<f32>(2.0, -2.0) + vec2<f32>(0.5, 0.5);
+    let screen = v.rect.xy + corner * v.rect.zw;
+    out.clip_position = vec4<f32>(screen / viewport.xy * vec2<f32>(2.0, -2.0) - vec2<f32>(1.0, -1.0), 1.0, 1.0);
+    out.uv = v.uv.xy + corner * v.uv.zw;
+    out.screen = screen;
+    out.clip = v.clip;
+    out.color = srgb_to_linear(v.color);
+    return out;
+}
+
+// Fragment shader
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSampleLevel(atlas, atlas_sampler, in.uv, 0.0).r;
+    if any(in.screen < in.clip.xy) || any(in.screen >= in.clip.xy + in.clip.zw) {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
@@ -0,0 +1,145 @@
+use ab_glyph::FontArc;
+
+use crate::{
+    color::Color,
+    floating::Align,
+    handle::{Handle, HandleLike},
+    units::UserUnits,
+};
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FontT {}
+
+pub type FontHandle = Handle<FontT>;
+
+/// sets the text drawn inside of a frame's visible bounds, unset fields keep their value
+#[derive(Clone, Default, Debug)]
+pub struct TextMessage {
+    /// an empty string draws nothing but keeps the style, lines are broken at `\n`
+    /// and wrapped at spaces
+    pub text: Option<String>,
+    /// frames without a font use the first one loaded
+    pub font: Option<FontHandle>,
+    /// em size like css's `font-size`, a ratio is relative to the frame's height
+    pub size: Option<UserUnits>,
+    pub color: Option<Color>,
+    /// `Start` is the right edge in right to left frames
+    pub align: Option<Align>,
+    pub vertical_align: Option<Align>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Text {
+    pub(crate) text: String,
+    pub(crate) font: Option<FontHandle>,
+    pub(crate) size: UserUnits,
+    pub(crate) color: Color,
+    pub(crate) align: Align,
+    pub(crate) vertical_align: Align,
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            font: None,
+            size: UserUnits::Pixel(16),
+            color: Color::BLACK,
+            align: Align::Start,
+            vertical_align: Align::Start,
+        }
+    }
+}
+
+/// loaded fonts and the text of every frame, laid out against the frames by `TextRenderer`
+#[derive(Default)]
+pub struct TextStore {
+    fonts: Vec<FontArc>,
+    texts: Vec<Option<Text>>,
+}
+
+impl TextStore {
+    pub fn add_font(&mut self, font: FontArc) -> FontHandle {
+        self.fonts.push(font);
+        FontHandle::new(self.fonts.len() - 1)
+    }
+    /// the font's index and the font, the first one without a handle
+    pub(crate) fn font(&self, handle: Option<FontHandle>) -> Option<(usize, &FontArc)> {
+        let index = handle.map_or(0, |h| h.index());
+        self.fonts.get(index).map(|f| (index, f))
+    }
+    /// returns whether the frame's text or its style changed
+    pub fn update(&mut self, frame: usize, msg: TextMessage) -> bool {
+        if self.texts.len() <= frame {
+            self.texts.resize(frame + 1, None);
+        }
+        let old = self.texts[frame].clone();
+        let TextMessage {
+            text,
+            font,
+            size,
+            color,
+            align,
+            vertical_align,
+        } = msg;
+        let t = self.texts[frame].get_or_insert_with(Text::default);
+        if let Some(text) = text {
+            t.text = text;
+        }
+        if let Some(font) = font {
+            t.font = Some(font);
+        }
+        if let Some(size) = size {
+            t.size = size;
+        }
+        if let Some(color) = color {
+            t.color = color;
+        }
+        if let Some(align) = align {
+            t.align = align;
+        }
+        if let Some(align) = vertical_align {
+            t.vertical_align = align;
+        }
+        old.as_ref() != Some(t)
+    }
+    pub(crate) fn get(&self, frame: usize) -> Option<&Text> {
+        self.texts.get(frame)?.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style_before_text_is_kept() {
+        let mut store = TextStore::default();
+        store.update(
+            2,
+            TextMessage {
+                color: Some(Color::WHITE),
+                align: Some(Align::Center),
+                ..TextMessage::default()
+            },
+        );
+        store.update(
+            2,
+            TextMessage {
+                text: Some("hi".into()),
+                ..TextMessage::default()
+            },
+        );
+        let t = store.get(2).unwrap();
+        assert_eq!((t.text.as_str(), t.color, t.align), ("hi", Color::WHITE, Align::Center));
+        store.update(
+            2,
+            TextMessage {
+                text: Some(String::new()),
+                ..TextMessage::default()
+            },
+        );
+        assert_eq!(store.get(2).unwrap().color, Color::WHITE);
+        assert!(store.get(0).is_none());
+    }
+}
@@ -1,20 +1,131 @@
 use std::{
     fmt,
-    ops::{Add, AddAssign, Div, Mul, Sub},
+    ops::{self, Add, AddAssign, Div, Mul, Sub},
 };
 
 use bytemuck::{Pod, Zeroable};
+use log::warn;
 
 pub type Pixelt = i32;
 pub type Ratiot = f32;
 pub type Fractiont = u32;
 
-#[derive(Debug, Clone, Copy)]
+/// lengths relative to a parent length, they can be combined with `+`, `-`, [`max`] and [`min`]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UserUnits {
     Zero,
     Pixel(Pixelt),
     Ratio(Ratiot),
+    /// share of the space left over in a grid direction, inside of an expression it counts as zero
     Fraction(Fractiont),
+    /// percent of the window's width
+    Vw(Ratiot),
+    /// percent of the window's height
+    Vh(Ratiot),
+    /// multiple of the root unit, 16 pixels unless changed with `UpdateMsg::RootUnit`
+    Rem(Ratiot),
+    Sum(Box<UserUnits>, Box<UserUnits>),
+    Diff(Box<UserUnits>, Box<UserUnits>),
+    Max(Box<UserUnits>, Box<UserUnits>),
+    Min(Box<UserUnits>, Box<UserUnits>),
+}
+
+/// the larger of `a` and `b`, a [`UserUnits::Fraction`] in either counts as zero
+pub fn max(a: UserUnits, b: UserUnits) -> UserUnits {
+    let (a, b) = operands("max", a, b);
+    UserUnits::Max(a, b)
+}
+
+/// the smaller of `a` and `b`, a [`UserUnits::Fraction`] in either counts as zero
+pub fn min(a: UserUnits, b: UserUnits) -> UserUnits {
+    let (a, b) = operands("min", a, b);
+    UserUnits::Min(a, b)
+}
+
+/// boxes both sides of an expression, warns when one of them holds a fraction
+/// because it will resolve to zero
+fn operands(op: &str, a: UserUnits, b: UserUnits) -> (Box<UserUnits>, Box<UserUnits>) {
+    if a.has_fraction() || b.has_fraction() {
+        warn!("a fraction inside of {op}({a:?}, {b:?}) counts as zero");
+    }
+    (Box::new(a), Box::new(b))
+}
+
+/// a [`UserUnits::Fraction`] on either side counts as zero
+impl ops::Add for UserUnits {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        let (a, b) = operands("+", self, rhs);
+        UserUnits::Sum(a, b)
+    }
+}
+
+/// a [`UserUnits::Fraction`] on either side counts as zero
+impl ops::Sub for UserUnits {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        let (a, b) = operands("-", self, rhs);
+        UserUnits::Diff(a, b)
+    }
+}
+
+impl From<i32> for UserUnits {
+    fn from(value: i32) -> Self {
+        UserUnits::Pixel(value)
+    }
+}
+
+/// the lengths viewport and root relative units are resolved against
+#[derive(Clone, Copy, Debug)]
+pub struct UnitContext {
+    pub viewport: [VUnit; 2],
+    pub rem: VUnit,
+}
+
+impl UnitContext {
+    pub const DEFAULT_REM: Pixelt = 16;
+}
+
+impl UserUnits {
+    fn has_fraction(&self) -> bool {
+        use UserUnits::*;
+        match self {
+            Fraction(_) => true,
+            Sum(a, b) | Diff(a, b) | Max(a, b) | Min(a, b) => a.has_fraction() || b.has_fraction(),
+            _ => false,
+        }
+    }
+    /// the length this resolves to inside of a parent `len` long
+    pub(crate) fn solve(&self, len: VUnit, ctx: &UnitContext) -> VUnit {
+        use UserUnits::*;
+        let scale = |len: VUnit, f: f32| -> VUnit { ((len.pix() * f).round() as i32).into() };
+        match self {
+            Zero | Fraction(_) => 0.into(),
+            Pixel(p) => (*p).into(),
+            Ratio(f) => scale(len, *f),
+            Vw(f) => scale(ctx.viewport[0], f / 100.0),
+            Vh(f) => scale(ctx.viewport[1], f / 100.0),
+            Rem(f) => scale(ctx.rem, *f),
+            Sum(a, b) => a.solve(len, ctx) + b.solve(len, ctx),
+            Diff(a, b) => a.solve(len, ctx) - b.solve(len, ctx),
+            Max(a, b) => a.solve(len, ctx).max(b.solve(len, ctx)),
+            Min(a, b) => a.solve(len, ctx).min(b.solve(len, ctx)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum SolveUnits {
+    Exact(VUnit),
+    Fraction(Fractiont),
+}
+
+/// only a bare `Fraction` shares the remaining space, everything else is exact
+pub(crate) fn units_solve(u: &UserUnits, len: VUnit, ctx: &UnitContext) -> SolveUnits {
+    match u {
+        UserUnits::Fraction(f) => SolveUnits::Fraction(*f),
+        u => SolveUnits::Exact(u.solve(len, ctx)),
+    }
 }
 
 /// i32 but 6 bits are for sub VUnit precision the max value is
@@ -74,6 +185,12 @@ impl From<u32> for VUnit {
     }
 }
 
+impl From<f32> for VUnit {
+    fn from(value: f32) -> Self {
+        VUnit((value * (1 << Self::PRECISION_BITS) as f32).round() as i32)
+    }
+}
+
 impl Add for VUnit {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output {
@@ -132,4 +249,85 @@ impl fmt::Debug for VUnit {
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::{UserUnits::*, *};
+
+    const CTX: UnitContext = UnitContext {
+        viewport: [VUnit(800 << 6), VUnit(600 << 6)],
+        rem: VUnit(16 << 6),
+    };
+
+    fn solve(u: UserUnits, len: i32) -> f32 {
+        u.solve(len.into(), &CTX).pix()
+    }
+
+    #[test]
+    fn expressions_solve_against_the_parent() {
+        assert_eq!(solve(Ratio(0.5) - Pixel(20), 300), 130.0);
+        assert_eq!(solve(max(Pixel(200), Ratio(0.3)), 500), 200.0);
+        assert_eq!(solve(max(Pixel(200), Ratio(0.3)), 1000), 300.0);
+        assert_eq!(solve(min(Pixel(200), Ratio(0.3)), 1000), 200.0);
+        let nested = min(Ratio(1.0) - Pixel(40), max(Pixel(100), Ratio(0.25) + Pixel(10)));
+        assert_eq!(solve(nested.clone(), 200), 100.0);
+        assert_eq!(solve(nested.clone(), 600), 160.0);
+        assert_eq!(solve(nested, 120), 80.0);
+        assert_eq!(solve(Zero - Pixel(5), 100), -5.0);
+    }
+
+    #[test]
+    fn nested_fractions_count_as_zero() {
+        assert_eq!(solve(Fraction(1) + Pixel(10), 300), 10.0);
+        assert_eq!(solve(max(Fraction(2), Ratio(0.1)), 300), 30.0);
+        assert!((Fraction(1) + Pixel(10)).has_fraction());
+        assert!(max(Pixel(1), min(Pixel(2), Fraction(1))).has_fraction());
+        assert!(!(Ratio(0.5) - Pixel(20)).has_fraction());
+    }
+
+    #[test]
+    fn viewport_and_root_units() {
+        assert_eq!(solve(Vw(10.0), 0), 80.0);
+        assert_eq!(solve(Vh(50.0), 0), 300.0);
+        assert_eq!(solve(Rem(1.5), 0), 24.0);
+        assert_eq!(solve(Vw(10.0) + Rem(1.0) - Ratio(0.5), 100), 46.0);
+    }
+
+    #[test]
+    fn units_follow_the_root_frame() {
+        use crate::{
+            frame::{FrameData, FrameStore},
+            manager::BBox,
+        };
+
+        let mut store = FrameStore::new();
+        store.add(FrameData::zeroed());
+        let root = |w: i32, h: i32| BBox {
+            x: 0.into(),
+            y: 0.into(),
+            w: w.into(),
+            h: h.into(),
+        };
+        store.update(0, &root(800, 600));
+        let vw = |store: &FrameStore| Vw(50.0).solve(0.into(), &store.unit_context()).pix();
+        let vh = |store: &FrameStore| Vh(50.0).solve(0.into(), &store.unit_context()).pix();
+        assert_eq!((vw(&store), vh(&store)), (400.0, 300.0));
+        store.update(0, &root(1000, 200));
+        assert_eq!((vw(&store), vh(&store)), (500.0, 100.0));
+        let rem = |store: &FrameStore| Rem(2.0).solve(0.into(), &store.unit_context()).pix();
+        assert_eq!(rem(&store), 32.0);
+        store.set_rem(20.into());
+        assert_eq!(rem(&store), 40.0);
+    }
+
+    #[test]
+    fn only_bare_fractions_share_the_space() {
+        assert!(matches!(units_solve(&Fraction(3), 100.into(), &CTX), SolveUnits::Fraction(3)));
+        match units_solve(&(Fraction(1) + Pixel(10)), 100.into(), &CTX) {
+            SolveUnits::Exact(v) => assert_eq!(v.pix(), 10.0),
+            SolveUnits::Fraction(_) => panic!("nested fractions are exact"),
+        }
+        match units_solve(&(Ratio(0.5) - Pixel(20)), 300.into(), &CTX) {
+            SolveUnits::Exact(v) => assert_eq!(v.pix(), 130.0),
+            SolveUnits::Fraction(_) => panic!("expressions are exact"),
+        }
+    }
+}
@@ -4,18 +4,15 @@ use winit::dpi::LogicalSize;
 
 use crate::{
     component::ComponentType,
-    grid::{XName, YName},
-    manager::BBox,
+    grid::{Direction, XName, YName},
+    images::{ImageHandle, ImageMessage},
+    layout::LayoutSnapshot,
+    manager::RedrawMode,
     render_actor::{FrameMessage, UpdateMessage},
+    text::TextMessage,
     units::UserUnits,
 };
 
-#[derive(Clone)]
-pub enum Bounds {
-    Rel(BBox),
-    Abs(BBox),
-}
-
 pub enum SystemUpdates {
     Resized(LogicalSize<u32>, f64)
 }
@@ -28,7 +25,20 @@ pub enum Update {
 
 #[derive(Clone)]
 pub enum UpdateMsg {
-    Frame(FrameMessage),
+    /// boxed since it is far larger than every other message
+    Frame(Box<FrameMessage>),
+    Text(TextMessage),
+    Image(ImageMessage),
+    /// frees the image's gpu memory, frames still showing it draw nothing
+    RemoveImage(ImageHandle),
     GridX(XName, UserUnits),
     GridY(YName, UserUnits),
+    DumpLayout(mpsc::Sender<LayoutSnapshot>),
+    DebugOverlay(bool),
+    RootUnit(f32),
+    Direction(Direction),
+    /// `RedrawMode::Continuous` draws every refresh instead of only after changes
+    RedrawMode(RedrawMode),
+    /// `true` starts an animation and `false` ends one, the window redraws every refresh while any are running
+    Animating(bool),
 }
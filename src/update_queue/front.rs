@@ -21,11 +21,58 @@ impl UpdateQueue {
         match msg {
             Update::User(msg, dst ) => match msg {
                 UpdateMsg::Frame(f) => {
-                    if let Err(e) = self.sender.send(UpdateMessage::ModifyFrame(dst.frame(), f)) {
+                    if let Err(e) = self.sender.send(UpdateMessage::ModifyFrame(dst.frame(), *f)) {
                         warn!("{e}");
                         exit(0);
                     }
                 }
+                UpdateMsg::Text(t) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::ModifyText(dst.frame(), t)) {
+                        warn!("{e}");
+                        exit(0);
+                    }
+                }
+                UpdateMsg::Image(i) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::ModifyImage(dst.frame(), i)) {
+                        warn!("{e}");
+                        exit(0);
+                    }
+                }
+                UpdateMsg::RemoveImage(image) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::RemoveImage(image)) {
+                        warn!("{e}");
+                    }
+                }
+                UpdateMsg::DumpLayout(send) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::DumpLayout(send)) {
+                        warn!("{e}");
+                    }
+                }
+                UpdateMsg::DebugOverlay(enabled) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::DebugOverlay(Some(enabled))) {
+                        warn!("{e}");
+                    }
+                }
+                UpdateMsg::RootUnit(rem) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::RootUnit(rem)) {
+                        warn!("{e}");
+                    }
+                }
+                UpdateMsg::Direction(direction) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::Direction(direction)) {
+                        warn!("{e}");
+                    }
+                }
+                UpdateMsg::RedrawMode(mode) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::RedrawMode(mode)) {
+                        warn!("{e}");
+                    }
+                }
+                UpdateMsg::Animating(running) => {
+                    if let Err(e) = self.sender.send(UpdateMessage::Animating(running)) {
+                        warn!("{e}");
+                    }
+                }
                 _ => (),
             }
             Update::System(msg) => match  msg {